@@ -12,6 +12,7 @@ mod plugin;
 pub use default_focus::handle_default_focus;
 pub use default_focus::reset_default_focus;
 pub use default_focus::IaDefaultFocus;
+pub use handle::clear_focus_effect;
 pub use handle::IaButton;
 pub use handle::IaCrossPanel;
 pub use handle::IaLink;
@@ -26,6 +26,15 @@ pub struct IaCrossPanel;
 #[derive(Component)]
 pub struct IaAnimeEffect;
 
+// NOTE: Only the focus effects of the menus, the other effects on the screen are kept
+pub fn clear_focus_effect(mut commands: Commands, ae_query: Query<Entity, With<IaAnimeEffect>>) {
+    for ae_entity in ae_query.iter() {
+        if let Some(entity_commands) = commands.get_entity(ae_entity) {
+            entity_commands.despawn_recursive()
+        }
+    }
+}
+
 #[derive(Default)]
 struct FocusTarget {
     pub pos: Vec2,
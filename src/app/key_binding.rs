@@ -41,9 +41,9 @@ fn handle_key_binding(
     input: Res<ButtonInput<KeyCode>>,
     config: Res<KeyBindingConfig>,
 ) {
-    move_by_arrow(&mut requests, &input);
     match config.mode {
         KeyBindingMode::Navgation => {
+            move_by_arrow(&mut requests, &input);
             if input.any_just_pressed([KeyCode::Space]) {
                 requests.send(NavRequest::Action);
             }
@@ -53,18 +53,14 @@ fn handle_key_binding(
             move_by_wsad(&mut requests, &input);
             move_by_kjhl(&mut requests, &input);
         }
+        // NOTE: Arrow keys and space are used to aim and launch in the game, escape pauses it
         KeyBindingMode::Gaming => {
-            if input.any_just_pressed([
-                KeyCode::Space,
-                KeyCode::Backspace,
-                KeyCode::Delete,
-                KeyCode::Escape,
-            ]) {
+            if input.any_just_pressed([KeyCode::Backspace, KeyCode::Delete]) {
                 requests.send(NavRequest::Action);
             }
         }
         // NOTE: use default key binding only
-        KeyBindingMode::Keyboard => (),
+        KeyBindingMode::Keyboard => move_by_arrow(&mut requests, &input),
     }
 }
 
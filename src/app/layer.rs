@@ -4,6 +4,7 @@ pub const PAGE_UI_Z_INDEX: i32 = 20;
 pub const ELEMENT_FG_Z_INDEX: f32 = 90.0;
 pub const ANIME_EFFECT_Z_INDEX: f32 = 80.0;
 pub const ELEMENT_BG_Z_INDEX: f32 = 70.0;
+pub const PAGE_GRAPH_Z_INDEX: f32 = 40.0;
 pub const GAME_COVER_Z_INDEX: f32 = 30.0;
 pub const GAME_FG_Z_INDEX: f32 = 20.0;
 pub const GAME_BG_Z_INDEX: f32 = 10.0;
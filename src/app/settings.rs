@@ -15,6 +15,12 @@ pub struct Settings {
     sensitivity: u8,
     sensitivity_modified: u8,
     last_player: String,
+    #[serde(default = "default_ghost_enabled")]
+    ghost_enabled: bool,
+}
+
+fn default_ghost_enabled() -> bool {
+    true
 }
 
 impl Settings {
@@ -24,6 +30,7 @@ impl Settings {
             "bgm" => self.bgm_enabled,
             "se" => self.se_enabled,
             "fullscreen" => self.fullscreen_enabled,
+            "ghost" => self.ghost_enabled,
             _ => false,
         }
     }
@@ -33,6 +40,7 @@ impl Settings {
             "bgm" => self.bgm_enabled = !self.bgm_enabled,
             "se" => self.se_enabled = !self.se_enabled,
             "fullscreen" => self.fullscreen_enabled = !self.fullscreen_enabled,
+            "ghost" => self.ghost_enabled = !self.ghost_enabled,
             _ => println!("Invalid field"),
         }
     }
@@ -110,6 +118,7 @@ impl Plugin for SettingsPlugin {
                     sensitivity: 50,
                     sensitivity_modified: 10,
                     last_player: String::from(""),
+                    ghost_enabled: default_ghost_enabled(),
                 })
                 .revertible(true)
                 .revert_to_default_on_deserialization_errors(true)
@@ -0,0 +1,80 @@
+use mechanics::game::{generate_level, generated_dir_path, GeneratorConfig, LEVEL_EXT};
+use std::{env, fs, path::PathBuf, process::ExitCode};
+
+const ASSETS_LEVEL_DIR: &str = "assets/levels";
+const USAGE: &str = "usage: generate_level [--seed <seed>] [--difficulty <0.0..1.0>] \
+    [--out <level file> | --assets]";
+
+fn main() -> ExitCode {
+    let mut config = GeneratorConfig {
+        seed: rand::random(),
+        ..Default::default()
+    };
+    let mut out: Option<PathBuf> = None;
+    let mut is_asset = false;
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--seed" => match args.next().and_then(|seed| seed.parse().ok()) {
+                Some(seed) => config.seed = seed,
+                None => return usage_error(),
+            },
+            "--difficulty" => match args.next().and_then(|difficulty| difficulty.parse().ok()) {
+                Some(difficulty) => config.difficulty = difficulty,
+                None => return usage_error(),
+            },
+            "--out" => match args.next() {
+                Some(path) => out = Some(PathBuf::from(path)),
+                None => return usage_error(),
+            },
+            "--assets" => is_asset = true,
+            "--help" | "-h" => {
+                println!("{}", USAGE);
+                return ExitCode::SUCCESS;
+            }
+            _ => return usage_error(),
+        }
+    }
+    let (level, report) = match generate_level(&config) {
+        Ok(generated) => generated,
+        Err(error) => {
+            eprintln!("seed {}: {}", config.seed, error);
+            return ExitCode::FAILURE;
+        }
+    };
+    // NOTE: Levels go to the user folder by default, the shipped levels are only written to with
+    // --assets
+    let path = match out {
+        Some(path) => path,
+        None => {
+            let level_dir = if is_asset {
+                PathBuf::from(ASSETS_LEVEL_DIR)
+            } else {
+                generated_dir_path()
+            };
+            if let Err(error) = fs::create_dir_all(&level_dir) {
+                eprintln!("{:?}: {}", level_dir, error);
+                return ExitCode::FAILURE;
+            }
+            level_dir.join(format!("{}.{}", level.code, LEVEL_EXT))
+        }
+    };
+    if let Err(error) = level.save(&path) {
+        eprintln!("{:?}: {}", path, error);
+        return ExitCode::FAILURE;
+    }
+    println!(
+        "{:?}: seed {}, win {:.1}% (target {:.1}%), fastest {:0>4}",
+        path,
+        config.seed,
+        report.win_fraction() * 100.0,
+        config.target_win_fraction() * 100.0,
+        report.fastest.map_or(0, |solution| solution.time),
+    );
+    ExitCode::SUCCESS
+}
+
+fn usage_error() -> ExitCode {
+    eprintln!("{}", USAGE);
+    ExitCode::FAILURE
+}
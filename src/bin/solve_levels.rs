@@ -0,0 +1,130 @@
+use mechanics::game::{solve, LevelCatalog, LevelConfig, SolverConfig, CATALOG_EXT, LEVEL_EXT};
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+
+const DEFAULT_LEVEL_DIR: &str = "assets/levels";
+const USAGE: &str = "usage: solve_levels [--dir <level dir>] [--max-ticks <ticks>] [level code...]";
+
+// NOTE: Check the levels headlessly, the levels in the catalogues are checked if no code is given.
+// The exit code is a failure if any level can not be loaded or solved.
+fn main() -> ExitCode {
+    let mut level_dir = PathBuf::from(DEFAULT_LEVEL_DIR);
+    let mut config = SolverConfig::default();
+    let mut codes: Vec<String> = vec![];
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--dir" => match args.next() {
+                Some(dir) => level_dir = PathBuf::from(dir),
+                None => return usage_error(),
+            },
+            "--max-ticks" => match args.next().and_then(|ticks| ticks.parse().ok()) {
+                Some(ticks) => config.max_ticks = ticks,
+                None => return usage_error(),
+            },
+            "--help" | "-h" => {
+                println!("{}", USAGE);
+                return ExitCode::SUCCESS;
+            }
+            _ => codes.push(arg),
+        }
+    }
+    if codes.is_empty() {
+        codes = match catalog_codes(&level_dir) {
+            Ok(codes) => codes,
+            Err(error) => {
+                eprintln!(
+                    "could not read the catalogues in {:?}: {}",
+                    level_dir, error
+                );
+                return ExitCode::FAILURE;
+            }
+        };
+    }
+    let mut is_all_solved = true;
+    for code in codes.iter() {
+        let path = level_dir.join(format!("{}.{}", code, LEVEL_EXT));
+        let level = match fs::read(&path)
+            .map_err(|error| error.to_string())
+            .and_then(|bytes| {
+                LevelConfig::from_bytes(code, &bytes).map_err(|error| error.to_string())
+            }) {
+            Ok(level) => level,
+            Err(error) => {
+                println!("{:<12} ERROR     {}", code, error);
+                is_all_solved = false;
+                continue;
+            }
+        };
+        let report = solve(&level, &config);
+        let Some(fastest) = &report.fastest else {
+            println!(
+                "{:<12} UNSOLVED  {} samples",
+                code,
+                report.grid_samples + report.refine_samples
+            );
+            is_all_solved = false;
+            continue;
+        };
+        let suggested_par = report
+            .suggested_par()
+            .and_then(|par| ron::to_string(&par).ok())
+            .unwrap_or_default();
+        let current_par = level
+            .par
+            .as_ref()
+            .and_then(|par| ron::to_string(par).ok())
+            .unwrap_or(String::from("-"));
+        println!(
+            "{:<12} SOLVED    time {:0>4}, collisions {}, force {:.2}, win {:.1}%, par {} (now {})",
+            code,
+            fastest.time,
+            fastest.collisions,
+            fastest.total_force(),
+            report.win_fraction() * 100.0,
+            suggested_par,
+            current_par,
+        );
+        println!(
+            "{:<12}           x {:?}, y {:?}, force {:?}, angle {:?}",
+            "", fastest.values.x, fastest.values.y, fastest.values.force, fastest.values.angle,
+        );
+    }
+    if is_all_solved {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
+
+fn usage_error() -> ExitCode {
+    eprintln!("{}", USAGE);
+    ExitCode::FAILURE
+}
+
+fn catalog_codes(level_dir: &Path) -> Result<Vec<String>, String> {
+    let mut codes: Vec<String> = vec![];
+    let entries = fs::read_dir(level_dir).map_err(|error| error.to_string())?;
+    for entry in entries {
+        let path = entry.map_err(|error| error.to_string())?.path();
+        let is_catalog = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .map_or(false, |name| name.ends_with(CATALOG_EXT));
+        if !is_catalog {
+            continue;
+        }
+        let bytes = fs::read(&path).map_err(|error| error.to_string())?;
+        let catalog: LevelCatalog =
+            ron::de::from_bytes(&bytes).map_err(|error| error.to_string())?;
+        for code in catalog.levels {
+            if !codes.contains(&code) {
+                codes.push(code);
+            }
+        }
+    }
+    Ok(codes)
+}
@@ -21,9 +21,11 @@ pub enum PageState {
     Help,
     Dev,
     Leaderboard,
+    Editor,
+    Kinematics,
 }
 
-pub const PAGES: [&dyn page::PageBase; 13] = [
+pub const PAGES: [&dyn page::PageBase; 15] = [
     &page::loading::Page,
     &page::menu::Page,
     &page::game::Page,
@@ -37,4 +39,6 @@ pub const PAGES: [&dyn page::PageBase; 13] = [
     &page::help::Page,
     &page::dev::Page,
     &page::leaderboard::Page,
+    &page::editor::Page,
+    &page::kinematics::Page,
 ];
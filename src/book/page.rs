@@ -5,8 +5,10 @@ use crate::{
 
 pub mod about;
 pub mod dev;
+pub mod editor;
 pub mod game;
 pub mod help;
+pub mod kinematics;
 pub mod leaderboard;
 pub mod level;
 pub mod loading;
@@ -191,3 +193,32 @@ fn build_sep_title(
         })
         .id()
 }
+
+const STAR_DIM_ALPHA: f32 = 0.2;
+
+fn build_stars(parent: &mut ChildBuilder, asset_server: &Res<AssetServer>, stars: u8, size: f32) {
+    parent
+        .spawn(NodeBundle {
+            style: Style {
+                align_items: AlignItems::Center,
+                column_gap: ui::px_p(1.0),
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|parent| {
+            for i in 0..crate::game::MAX_STARS {
+                let alpha = if i < stars { 1.0 } else { STAR_DIM_ALPHA };
+                parent.spawn(ImageBundle {
+                    style: Style {
+                        width: Val::Px(size),
+                        height: Val::Px(size),
+                        ..default()
+                    },
+                    image: UiImage::new(asset_server.load("images/icons/star-light.png"))
+                        .with_color(Color::WHITE.with_alpha(alpha)),
+                    ..default()
+                });
+            }
+        });
+}
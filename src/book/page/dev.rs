@@ -1,4 +1,5 @@
 use super::*;
+use crate::game::DebugOverlay;
 use bevy_alt_ui_navigation_lite::{prelude::*, NavRequestSystem};
 
 const PAGE_CODE: &str = "dev";
@@ -24,8 +25,10 @@ impl PageBase for Page {
         app.add_systems(OnEnter(self.state()), page_enter)
             .add_systems(
                 Update,
-                handle_ui_navigation
-                    .after(NavRequestSystem)
+                (
+                    handle_ui_navigation.after(NavRequestSystem),
+                    refresh_debug_overlay_text,
+                )
                     .run_if(in_state(self.state())),
             )
             .add_systems(
@@ -38,9 +41,13 @@ impl PageBase for Page {
 #[derive(Component)]
 struct OnPage;
 
+#[derive(Component)]
+struct DebugOverlayText;
+
 #[derive(Component)]
 enum ButtonAction {
     MoveToPage(PageState),
+    ToggleDebugOverlay,
 }
 
 const COLORS: [Color; 9] = [
@@ -208,6 +215,45 @@ fn page_enter(mut commands: Commands, asset_server: Res<AssetServer>) {
                                             });
                                     }
                                 });
+                            build_sep_title(parent, &asset_server, "Physics", "gauge-fill");
+                            parent
+                                .spawn(NodeBundle {
+                                    style: Style {
+                                        align_items: AlignItems::Center,
+                                        column_gap: ui::px_p(4.0),
+                                        margin: UiRect::vertical(ui::px_p(2.0)),
+                                        ..default()
+                                    },
+                                    ..default()
+                                })
+                                .with_children(|parent| {
+                                    ui::build_btn(
+                                        parent,
+                                        &asset_server,
+                                        (
+                                            ButtonAction::ToggleDebugOverlay,
+                                            app::interaction::IaButton,
+                                            Focusable::default(),
+                                        ),
+                                        Style {
+                                            padding: UiRect::all(ui::px_p(ui::BTN_PADDING)),
+                                            ..default()
+                                        },
+                                        Some("DEBUG OVERLAY"),
+                                        None,
+                                    );
+                                    parent.spawn((
+                                        TextBundle::from_section(
+                                            "",
+                                            TextStyle {
+                                                font: asset_server.load(theme::FONT),
+                                                font_size: ui::FONT_SIZE,
+                                                color: theme::SECONDARY_COLOR,
+                                            },
+                                        ),
+                                        DebugOverlayText,
+                                    ));
+                                });
                         });
                 });
             ui::build_icon_btn(
@@ -233,11 +279,32 @@ fn handle_ui_navigation(
     mut actions: Query<&mut ButtonAction>,
     mut events: EventReader<NavEvent>,
     mut page_state: ResMut<NextState<PageState>>,
+    mut debug_overlay: ResMut<DebugOverlay>,
 ) {
     events.nav_iter().activated_in_query_foreach_mut(
         &mut actions,
         |mut action| match &mut *action {
             ButtonAction::MoveToPage(state) => page_state.set(*state),
+            ButtonAction::ToggleDebugOverlay => debug_overlay.toggle(),
         },
     );
 }
+
+fn refresh_debug_overlay_text(
+    mut text_query: Query<&mut Text, With<DebugOverlayText>>,
+    debug_overlay: Res<DebugOverlay>,
+    mut last_enabled: Local<Option<bool>>,
+) {
+    if *last_enabled == Some(debug_overlay.is_enabled) && !text_query.is_empty() {
+        return;
+    }
+    *last_enabled = Some(debug_overlay.is_enabled);
+    let state = if debug_overlay.is_enabled {
+        "ON"
+    } else {
+        "OFF"
+    };
+    for mut text in text_query.iter_mut() {
+        text.sections[0].value = format!("{} (F3 IN GAME)", state);
+    }
+}
@@ -0,0 +1,387 @@
+use crate::{app::anime_effect, app::key_binding, app::theme::*, app::ui, book::page::*, game::*};
+use bevy_alt_ui_navigation_lite::{prelude::*, NavRequestSystem};
+
+const PAGE_CODE: &str = "editor";
+const PAGE_NAME: &str = "Editor";
+const PAGE_ICON: &str = "compass-tool-fill";
+
+pub struct Page;
+
+impl PageBase for Page {
+    fn code(&self) -> &str {
+        PAGE_CODE
+    }
+    fn name(&self) -> &str {
+        PAGE_NAME
+    }
+    fn icon(&self) -> &str {
+        PAGE_ICON
+    }
+    fn state(&self) -> PageState {
+        PageState::Editor
+    }
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(self.state()), page_enter)
+            .add_systems(
+                Update,
+                (
+                    handle_ui_navigation.after(NavRequestSystem),
+                    handle_level_drop,
+                    handle_editing,
+                    handle_editor_keys,
+                    refresh_editor_view,
+                    refresh_editor_info,
+                )
+                    .run_if(in_state(self.state())),
+            )
+            .add_systems(
+                OnExit(self.state()),
+                (
+                    anime_effect::clear_anime_effect,
+                    ui::despawn_ui::<OnPage>,
+                    ui::despawn_ui::<EditorView>,
+                    page_exit,
+                ),
+            );
+    }
+}
+
+#[derive(Component)]
+struct OnPage;
+
+#[derive(Component)]
+struct EditorInfo;
+
+#[derive(Component)]
+struct EditorMessage;
+
+#[derive(Component)]
+enum ButtonAction {
+    BackToMainMenu,
+    SetBallType(usize),
+    SetMovementType(BallMovementType),
+    Undo,
+    Redo,
+    ToggleSnap,
+    EditText(EditorTextField),
+    New,
+    Open,
+    Load,
+    Save,
+    Test,
+}
+
+const TOOL_FS: f32 = ui::FONT_SIZE * 0.6;
+const TOOL_W: f32 = ui::FONT_SIZE * 6.0;
+const EDITOR_HELP: &str = "CLICK TO ADD OR SELECT, DRAG TO MOVE, DRAG HANDLES TO RESIZE AND AIM\n\
+    DEL DELETE, CTRL+Z UNDO, CTRL+Y REDO, G SNAP, TAB NEXT, 1-4 TYPE, M MOVEMENT\n\
+    X POSITION RANGE, L LAUNCH RANGE, DROP A LEVEL FILE TO LOAD IT\n\
+    CODE AND NAME TO TYPE THEM, SAVE AND LOAD USE THE CODE";
+
+fn page_enter(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut key_binding: ResMut<key_binding::KeyBindingConfig>,
+    mut editor: ResMut<EditorStatus>,
+) {
+    // NOTE: Arrow keys and enter still work on the tool buttons, other keys are editing shortcuts
+    key_binding.mode = key_binding::KeyBindingMode::Keyboard;
+    editor.message = String::new();
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    ..default()
+                },
+                ..default()
+            },
+            OnPage,
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        top: ui::px_p(ui::PAGE_PADDING),
+                        right: ui::px_p(ui::PAGE_PADDING),
+                        flex_direction: FlexDirection::Column,
+                        row_gap: ui::px_p(1.0),
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|parent| {
+                    let tools = [
+                        (ButtonAction::SetBallType(0), "STONE"),
+                        (ButtonAction::SetBallType(1), "GOAL"),
+                        (ButtonAction::SetBallType(2), "BOMB"),
+                        (ButtonAction::SetBallType(3), "ATTRACTOR"),
+                        (
+                            ButtonAction::SetMovementType(BallMovementType::Movable),
+                            "MOVABLE",
+                        ),
+                        (
+                            ButtonAction::SetMovementType(BallMovementType::Fixed),
+                            "FIXED",
+                        ),
+                        (
+                            ButtonAction::SetMovementType(BallMovementType::FixedReversed),
+                            "REVERSED",
+                        ),
+                        (ButtonAction::Undo, "UNDO"),
+                        (ButtonAction::Redo, "REDO"),
+                        (ButtonAction::ToggleSnap, "SNAP"),
+                        (ButtonAction::EditText(EditorTextField::Code), "CODE"),
+                        (ButtonAction::EditText(EditorTextField::Name), "NAME"),
+                        (ButtonAction::New, "NEW"),
+                        (ButtonAction::Open, "OPEN"),
+                        (ButtonAction::Load, "LOAD"),
+                        (ButtonAction::Save, "SAVE"),
+                        (ButtonAction::Test, "TEST"),
+                    ];
+                    for (action, text) in tools {
+                        ui::build_btn(
+                            parent,
+                            &asset_server,
+                            (action, app::interaction::IaButton, Focusable::default()),
+                            Style {
+                                width: Val::Px(TOOL_W),
+                                padding: UiRect::all(ui::px_p(ui::BTN_PADDING * 0.5)),
+                                ..default()
+                            },
+                            Some(text),
+                            None,
+                        );
+                    }
+                });
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        bottom: ui::px_p(ui::PAGE_PADDING),
+                        right: ui::px_p(ui::PAGE_PADDING),
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::End,
+                        row_gap: ui::px_p(2.0),
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|parent| {
+                    parent.spawn((
+                        TextBundle::from_section(
+                            "",
+                            TextStyle {
+                                font: asset_server.load(FONT),
+                                font_size: TOOL_FS,
+                                color: FG_COLOR,
+                            },
+                        ),
+                        EditorInfo,
+                    ));
+                    parent.spawn((
+                        TextBundle::from_section(
+                            "",
+                            TextStyle {
+                                font: asset_server.load(FONT),
+                                font_size: TOOL_FS,
+                                color: HIGHLIGHT_COLOR,
+                            },
+                        ),
+                        EditorMessage,
+                    ));
+                    parent.spawn(
+                        TextBundle::from_section(
+                            EDITOR_HELP,
+                            TextStyle {
+                                font: asset_server.load(FONT),
+                                font_size: TOOL_FS,
+                                color: SECONDARY_COLOR,
+                            },
+                        )
+                        .with_text_justify(JustifyText::Right),
+                    );
+                });
+            ui::build_icon_btn(
+                parent,
+                &asset_server,
+                (
+                    ButtonAction::BackToMainMenu,
+                    app::interaction::IaButton,
+                    Focusable::new().prioritized(),
+                ),
+                Style {
+                    position_type: PositionType::Absolute,
+                    bottom: ui::px_p(ui::PAGE_PADDING),
+                    left: ui::px_p(ui::PAGE_PADDING),
+                    ..default()
+                },
+                "arrow-left-light_1.5x",
+            );
+        });
+}
+
+fn page_exit(mut key_binding: ResMut<key_binding::KeyBindingConfig>) {
+    key_binding.mode = key_binding::KeyBindingMode::Navgation;
+}
+
+fn handle_ui_navigation(
+    mut actions: Query<&mut ButtonAction>,
+    mut events: EventReader<NavEvent>,
+    mut page_state: ResMut<NextState<PageState>>,
+    mut level_assets: ResMut<Assets<LevelConfig>>,
+    mut game_status: ResMut<GameStatus>,
+    mut editor: ResMut<EditorStatus>,
+) {
+    events
+        .nav_iter()
+        .activated_in_query_foreach_mut(&mut actions, |mut action| {
+            // NOTE: Any button ends the typing, the field button itself toggles it
+            let text_field = editor.finish_text_edit();
+            match &mut *action {
+                ButtonAction::BackToMainMenu => page_state.set(PageState::Menu),
+                ButtonAction::SetBallType(index) => {
+                    if let Some(ball_type) = ball_type_at(*index) {
+                        editor.set_ball_type(ball_type);
+                    }
+                }
+                ButtonAction::SetMovementType(movement_type) => {
+                    editor.set_movement_type(movement_type.clone());
+                }
+                ButtonAction::Undo => editor.undo(),
+                ButtonAction::Redo => editor.redo(),
+                ButtonAction::ToggleSnap => editor.toggle_snap(),
+                ButtonAction::EditText(field) => {
+                    if text_field != Some(*field) {
+                        editor.start_text_edit(*field);
+                    }
+                }
+                ButtonAction::New => {
+                    editor.open(new_level());
+                    editor.message = String::from("NEW LEVEL");
+                }
+                // NOTE: Open a copy of the last chosen level
+                ButtonAction::Open => match fetch_level(&level_assets, &game_status.level_code) {
+                    Some((_, level)) if level.code != EDITOR_LEVEL_CODE => {
+                        editor.open(level.clone());
+                        editor.message = format!("OPENED {}", level.code);
+                    }
+                    _ => editor.message = String::from("NO LEVEL CHOSEN TO OPEN"),
+                },
+                ButtonAction::Load => {
+                    let path = saved_editor_level_path(&editor.level.code);
+                    match load_editor_level(&path) {
+                        Ok(level) => {
+                            editor.open(level);
+                            editor.message = format!("LOADED {}", path.to_string_lossy());
+                        }
+                        Err(error) => editor.message = error.to_string().to_uppercase(),
+                    }
+                }
+                ButtonAction::Save => {
+                    // NOTE: The test play code is taken by the level asset of the test plays
+                    if editor.level.code.is_empty() || editor.level.code == EDITOR_LEVEL_CODE {
+                        editor.message = String::from("CHOOSE ANOTHER LEVEL CODE TO SAVE");
+                        return;
+                    }
+                    let result = editor
+                        .level
+                        .validate()
+                        .map_err(|error| error.to_string())
+                        .and_then(|_| {
+                            save_editor_level(&editor.level).map_err(|error| error.to_string())
+                        });
+                    editor.message = match result {
+                        Ok(path) => format!("SAVED TO {}", path.to_string_lossy()),
+                        Err(error) => error.to_uppercase(),
+                    };
+                }
+                // NOTE: Test plays use a level asset of their own so the shipped levels are untouched
+                ButtonAction::Test => {
+                    let level = editor.test_level();
+                    if let Err(error) = level.validate() {
+                        editor.message = error.to_string().to_uppercase();
+                        return;
+                    }
+                    match editor.test_handle.clone() {
+                        Some(handle) => level_assets.insert(handle.id(), level),
+                        None => editor.test_handle = Some(level_assets.add(level)),
+                    }
+                    game_status.level_code = String::from(EDITOR_LEVEL_CODE);
+                    game_status.is_test_play = true;
+                    page_state.set(PageState::Game);
+                }
+            }
+        });
+}
+
+fn handle_level_drop(
+    mut drop_events: EventReader<FileDragAndDrop>,
+    mut editor: ResMut<EditorStatus>,
+) {
+    for event in drop_events.read() {
+        let FileDragAndDrop::DroppedFile { path_buf, .. } = event else {
+            continue;
+        };
+        if !path_buf.to_string_lossy().ends_with(LEVEL_EXT) {
+            continue;
+        }
+        match load_editor_level(path_buf) {
+            Ok(level) => {
+                editor.message = format!("LOADED {}", level.code);
+                editor.open(level);
+            }
+            Err(error) => editor.message = error.to_string().to_uppercase(),
+        }
+    }
+}
+
+fn refresh_editor_info(
+    mut info_query: Query<&mut Text, (With<EditorInfo>, Without<EditorMessage>)>,
+    mut message_query: Query<&mut Text, (With<EditorMessage>, Without<EditorInfo>)>,
+    editor: Res<EditorStatus>,
+) {
+    if !editor.is_changed() {
+        return;
+    }
+    let selected = editor
+        .selected
+        .and_then(|index| editor.level.balls.get(index).map(|ball| (index, ball)))
+        .map_or(String::from("NONE"), |(index, ball)| {
+            format!(
+                "#{} {} {:?} R {:.0}",
+                index,
+                ball.ball_type.to_string().to_uppercase(),
+                ball.property.movement_type,
+                ball.property.radius
+            )
+            .to_uppercase()
+        });
+    let snap = if editor.is_snapped { "ON" } else { "OFF" };
+    let cursor = |field: EditorTextField| {
+        if editor.text_field() == Some(field) {
+            "_"
+        } else {
+            ""
+        }
+    };
+    if let Ok(mut text) = info_query.get_single_mut() {
+        text.sections[0].value = format!(
+            "{}{} ({}{}) | NEW {} {:?} | SNAP {} | SELECTED {}",
+            editor.level.name,
+            cursor(EditorTextField::Name),
+            editor.level.code,
+            cursor(EditorTextField::Code),
+            editor.ball_type.to_string().to_uppercase(),
+            editor.movement_type,
+            snap,
+            selected
+        )
+        .to_uppercase();
+    }
+    if let Ok(mut text) = message_query.get_single_mut() {
+        text.sections[0].value = editor.message.clone();
+    }
+}
@@ -1,5 +1,6 @@
-use crate::{app::anime_effect, book::page::*, game::*};
+use crate::{app::anime_effect, app::theme::*, book::page::*, game::*};
 use bevy_alt_ui_navigation_lite::{prelude::*, NavRequestSystem};
+use bevy_persistent::prelude::*;
 use bevy_prototype_lyon::path::PathBuilder;
 use bevy_prototype_lyon::prelude::*;
 
@@ -32,11 +33,23 @@ impl PageBase for Page {
                         .run_if(in_state(self.state())),
                 ),
             )
+            .add_systems(
+                OnEnter(PhaseState::GameOver),
+                (app::interaction::reset_default_focus, build_result_panel)
+                    .run_if(in_state(self.state())),
+            )
+            .add_systems(
+                Update,
+                app::interaction::handle_default_focus
+                    .run_if(in_state(self.state()).and_then(in_state(PhaseState::GameOver))),
+            )
+            .add_systems(OnExit(PhaseState::GameOver), ui::despawn_ui::<ResultPanel>)
             .add_systems(
                 OnExit(self.state()),
                 (
-                    anime_effect::clear_anime_effect,
+                    anime_effect::clear_anime_effect.run_if(not(is_in_pause_settings)),
                     ui::despawn_ui::<OnPage>,
+                    ui::despawn_ui::<ResultPanel>,
                     page_exit,
                 ),
             );
@@ -46,9 +59,15 @@ impl PageBase for Page {
 #[derive(Component)]
 struct OnPage;
 
+#[derive(Component)]
+struct ResultPanel;
+
 #[derive(Component)]
 enum ButtonAction {
     MoveToPage(PageState),
+    Retry,
+    NextLevel(String),
+    Replay,
 }
 
 const LV_CIRCLE_R: f32 = ui::FONT_SIZE * 3.0;
@@ -63,6 +82,12 @@ const LV_TITLE_W: f32 = ui::FONT_SIZE * 12.0;
 const LV_SHADOW_OFFSET: f32 = LV_LINE_W;
 const LV_SHADOW_W: f32 = LV_LINE_W * 0.5;
 
+const RESULT_TITLE_FS: f32 = ui::FONT_SIZE * 1.5;
+const RESULT_TIME_FS: f32 = ui::FONT_SIZE;
+const RESULT_STAR_SIZE: f32 = ui::FONT_SIZE * 1.5;
+const RESULT_INFO_FS: f32 = ui::FONT_SIZE * 0.6;
+const RESULT_BTN_W: f32 = ui::FONT_SIZE * 5.0;
+
 const GAME_FG_REFRESH_TARGET: &str = "GAME_FG_REFRESH_TARGET";
 
 fn page_enter(
@@ -71,31 +96,37 @@ fn page_enter(
     mut game_state: ResMut<NextState<PhaseState>>,
     mut game_status: ResMut<GameStatus>,
 ) {
-    game_state.set(PhaseState::Preparing);
-    game_status.mode = StatusMode::Setup;
-    game_status.control_displays = vec![
-        BallControlDisplay {
-            ball_type: Some(BallType::Stone),
-            control_type: BallControlType::Angle,
-            text: String::from("00.00"),
-        },
-        BallControlDisplay {
-            ball_type: Some(BallType::Stone),
-            control_type: BallControlType::Force,
-            text: String::from("00.00"),
-        },
-        BallControlDisplay {
-            ball_type: Some(BallType::Goal),
-            control_type: BallControlType::Move2D,
-            text: String::from("00.00\n00.00"),
-        },
-        BallControlDisplay {
-            ball_type: Some(BallType::Bomb),
-            control_type: BallControlType::Move1D,
-            text: String::from("00.00"),
-        },
-    ];
+    // NOTE: Back from the settings the level is still paused as it was left
+    if game_status.is_in_pause_settings {
+        game_status.is_in_pause_settings = false;
+    } else {
+        game_state.set(PhaseState::Preparing);
+        game_status.mode = StatusMode::Setup;
+        game_status.control_displays = vec![
+            BallControlDisplay {
+                ball_type: Some(BallType::Stone),
+                control_type: BallControlType::Angle,
+                text: String::from("00.00"),
+            },
+            BallControlDisplay {
+                ball_type: Some(BallType::Stone),
+                control_type: BallControlType::Force,
+                text: String::from("00.00"),
+            },
+            BallControlDisplay {
+                ball_type: Some(BallType::Goal),
+                control_type: BallControlType::Move2D,
+                text: String::from("00.00\n00.00"),
+            },
+            BallControlDisplay {
+                ball_type: Some(BallType::Bomb),
+                control_type: BallControlType::Move1D,
+                text: String::from("00.00"),
+            },
+        ];
+    }
     game_status.require_refresh(Some(ui::REFRESH_GAME_FG));
+    let is_test_play = game_status.is_test_play;
     commands
         .spawn((
             NodeBundle {
@@ -144,6 +175,19 @@ fn page_enter(
                         Style::default(),
                         "circles-four-light",
                     );
+                    if is_test_play {
+                        ui::build_icon_btn(
+                            parent,
+                            &asset_server,
+                            (
+                                ButtonAction::MoveToPage(PageState::Editor),
+                                app::interaction::IaButton,
+                                Focusable::new().prioritized(),
+                            ),
+                            Style::default(),
+                            "compass-tool-fill",
+                        );
+                    }
                 });
         });
 }
@@ -157,9 +201,214 @@ fn page_update(
 ) {
 }
 
+fn build_result_panel(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    catalog_assets: Res<Assets<LevelCatalog>>,
+    leaderboard: Res<Persistent<Leaderboard>>,
+    game_status: Res<GameStatus>,
+) {
+    let history = &game_status.current_history;
+    let (title, title_color) = if history.is_clear {
+        ("CLEAR", SUCCESS_COLOR)
+    } else {
+        ("FAILED", FAILURE_COLOR)
+    };
+    // NOTE: The history is stored before the game is over, so a new best is the stored one
+    let is_new_best = leaderboard
+        .level_info(game_status.current_level.code.clone())
+        .best_history
+        .map_or(false, |best| best.created_at == history.created_at);
+    let next_level = next_level_code(&catalog_assets, &leaderboard, &game_status);
+    let back_page = if game_status.is_test_play {
+        PageState::Editor
+    } else {
+        PageState::Level
+    };
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    top: ui::px_p(ui::PAGE_PADDING),
+                    width: Val::Percent(100.0),
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    row_gap: ui::px_p(2.0),
+                    ..default()
+                },
+                ..default()
+            },
+            ResultPanel,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                title,
+                TextStyle {
+                    font: asset_server.load(FONT),
+                    font_size: RESULT_TITLE_FS,
+                    color: title_color,
+                },
+            ));
+            build_stars(parent, &asset_server, history.stars, RESULT_STAR_SIZE);
+            parent.spawn(TextBundle::from_section(
+                format!("{:.2}s ({:0>4})", ticks_to_secs(history.time), history.time),
+                TextStyle {
+                    font: asset_server.load(FONT_DIGIT),
+                    font_size: RESULT_TIME_FS,
+                    color: FG_COLOR,
+                },
+            ));
+            if is_new_best {
+                parent.spawn(TextBundle::from_section(
+                    "NEW BEST",
+                    TextStyle {
+                        font: asset_server.load(FONT),
+                        font_size: RESULT_INFO_FS,
+                        color: HIGHLIGHT_COLOR,
+                    },
+                ));
+            }
+            parent.spawn(TextBundle::from_section(
+                controls_text(history),
+                TextStyle {
+                    font: asset_server.load(FONT),
+                    font_size: RESULT_INFO_FS,
+                    color: SECONDARY_COLOR,
+                },
+            ));
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Row,
+                        column_gap: ui::px_p(2.0),
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|parent| {
+                    let style = Style {
+                        width: Val::Px(RESULT_BTN_W),
+                        justify_content: JustifyContent::Center,
+                        padding: UiRect::all(ui::px_p(ui::BTN_PADDING)),
+                        ..default()
+                    };
+                    ui::build_btn(
+                        parent,
+                        &asset_server,
+                        (
+                            ButtonAction::Retry,
+                            app::interaction::IaButton,
+                            app::interaction::IaDefaultFocus,
+                            Focusable::default(),
+                        ),
+                        style.clone(),
+                        Some("RETRY"),
+                        None,
+                    );
+                    if let Some(code) = next_level {
+                        ui::build_btn(
+                            parent,
+                            &asset_server,
+                            (
+                                ButtonAction::NextLevel(code),
+                                app::interaction::IaButton,
+                                Focusable::default(),
+                            ),
+                            style.clone(),
+                            Some("NEXT"),
+                            None,
+                        );
+                    }
+                    ui::build_btn(
+                        parent,
+                        &asset_server,
+                        (
+                            ButtonAction::Replay,
+                            app::interaction::IaButton,
+                            Focusable::default(),
+                        ),
+                        style.clone(),
+                        Some("REPLAY"),
+                        None,
+                    );
+                    ui::build_btn(
+                        parent,
+                        &asset_server,
+                        (
+                            ButtonAction::MoveToPage(PageState::Kinematics),
+                            app::interaction::IaButton,
+                            Focusable::default(),
+                        ),
+                        style.clone(),
+                        Some("GRAPHS"),
+                        None,
+                    );
+                    ui::build_btn(
+                        parent,
+                        &asset_server,
+                        (
+                            ButtonAction::MoveToPage(back_page),
+                            app::interaction::IaButton,
+                            Focusable::default(),
+                        ),
+                        style,
+                        Some("BACK"),
+                        None,
+                    );
+                });
+        });
+}
+
+fn controls_text(history: &LevelHistory) -> String {
+    let fields = [
+        ("X", &history.x),
+        ("Y", &history.y),
+        ("FORCE", &history.force),
+        ("ANGLE", &history.angle),
+    ];
+    fields
+        .iter()
+        .filter(|(_, values)| !values.is_empty())
+        .map(|(name, values)| {
+            let values: Vec<String> = values.iter().map(|value| format!("{:.2}", value)).collect();
+            format!("{} {}", name, values.join(" "))
+        })
+        .collect::<Vec<String>>()
+        .join("  ")
+}
+
+// NOTE: Only offer the next level in the catalogue if it is already unlocked
+fn next_level_code(
+    catalog_assets: &Assets<LevelCatalog>,
+    leaderboard: &Leaderboard,
+    game_status: &GameStatus,
+) -> Option<String> {
+    if game_status.is_test_play {
+        return None;
+    }
+    let catalog = fetch_catalog(catalog_assets)?;
+    let index = catalog
+        .levels
+        .iter()
+        .position(|code| *code == game_status.current_level.code)?
+        + 1;
+    let code = catalog.levels.get(index)?;
+    let is_clear = |code: &str| leaderboard.level_info(String::from(code)).is_clear;
+    catalog
+        .is_unlocked(index, leaderboard.total_stars(), is_clear)
+        .then(|| code.clone())
+}
+
 fn page_exit(mut game_state: ResMut<NextState<PhaseState>>, mut game_status: ResMut<GameStatus>) {
+    if game_status.is_in_pause_settings {
+        return;
+    }
     game_state.set(PhaseState::Ready);
     game_status.mode = StatusMode::Demo;
+    game_status.replay = None;
+    game_status.is_test_play = false;
+    game_status.resume_mode = None;
     game_status.require_refresh(Some(ui::REFRESH_GAME_FG));
 }
 
@@ -167,11 +416,30 @@ fn handle_ui_navigation(
     mut actions: Query<&mut ButtonAction>,
     mut events: EventReader<NavEvent>,
     mut page_state: ResMut<NextState<PageState>>,
+    mut game_phase: ResMut<NextState<PhaseState>>,
+    mut game_status: ResMut<GameStatus>,
 ) {
     events.nav_iter().activated_in_query_foreach_mut(
         &mut actions,
         |mut action| match &mut *action {
             ButtonAction::MoveToPage(state) => page_state.set(*state),
+            ButtonAction::Retry => {
+                game_status.prepare_retry();
+                game_phase.set(PhaseState::Preparing);
+            }
+            ButtonAction::NextLevel(code) => {
+                game_status.level_code = code.clone();
+                game_status.replay = None;
+                game_phase.set(PhaseState::Preparing);
+            }
+            // NOTE: Replays of a replay restart the recorded attempt
+            ButtonAction::Replay => {
+                if game_status.replay.is_none() {
+                    game_status.replay =
+                        Some(ReplayStatus::new(game_status.current_history.clone()));
+                }
+                game_phase.set(PhaseState::Preparing);
+            }
         },
     );
 }
@@ -0,0 +1,346 @@
+use super::*;
+use crate::game::{KinematicsQuantity, KinematicsRecord};
+use bevy_alt_ui_navigation_lite::{prelude::*, NavRequestSystem};
+use bevy_prototype_lyon::prelude::*;
+
+const PAGE_CODE: &str = "kinematics";
+const PAGE_NAME: &str = "Kinematics";
+const PAGE_ICON: &str = "waveform-bold";
+
+pub struct Page;
+
+impl PageBase for Page {
+    fn code(&self) -> &str {
+        PAGE_CODE
+    }
+    fn name(&self) -> &str {
+        PAGE_NAME
+    }
+    fn icon(&self) -> &str {
+        PAGE_ICON
+    }
+    fn state(&self) -> PageState {
+        PageState::Kinematics
+    }
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(self.state()), (page_enter, build_graphs))
+            .add_systems(
+                Update,
+                handle_ui_navigation
+                    .after(NavRequestSystem)
+                    .run_if(in_state(self.state())),
+            )
+            .add_systems(
+                OnExit(self.state()),
+                (anime_effect::clear_anime_effect, ui::despawn_ui::<OnPage>),
+            );
+    }
+}
+
+#[derive(Component)]
+struct OnPage;
+
+#[derive(Component)]
+enum ButtonAction {
+    MoveToPage(PageState),
+}
+
+const GRAPH_COLORS: [Color; 5] = [
+    theme::U_COLOR,
+    theme::H_COLOR,
+    theme::C_COLOR,
+    theme::T_COLOR,
+    theme::LIGHT_COLOR,
+];
+const GRAPH_COLUMNS: usize = 2;
+const GRAPH_GAP: f32 = ui::FONT_SIZE * 0.6;
+const GRAPH_TOP_RATIO: f32 = 0.16;
+const GRAPH_BOTTOM_RATIO: f32 = 0.1;
+const GRAPH_LINE_W: f32 = ui::SPACE_SIZE * 0.4;
+const GRAPH_FRAME_W: f32 = ui::SPACE_SIZE * 0.3;
+const GRAPH_FS: f32 = ui::FONT_SIZE * 0.5;
+const GRAPH_LABEL_PADDING: f32 = ui::SPACE_SIZE * 2.0;
+
+fn page_enter(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    record: Res<KinematicsRecord>,
+) {
+    commands
+        .spawn((build_page_layout(), OnPage))
+        .with_children(|parent| {
+            build_game_title(parent, &asset_server);
+            build_page_title(parent, &asset_server, PAGE_NAME, PAGE_ICON);
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        bottom: ui::px_p(ui::PAGE_PADDING),
+                        right: ui::px_p(ui::PAGE_PADDING),
+                        column_gap: ui::px_p(4.0),
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|parent| {
+                    if record.is_empty() {
+                        parent.spawn(TextBundle::from_section(
+                            "NO RUN RECORDED",
+                            TextStyle {
+                                font: asset_server.load(theme::FONT),
+                                font_size: GRAPH_FS,
+                                color: theme::SECONDARY_COLOR,
+                            },
+                        ));
+                        return;
+                    }
+                    let mut header = format!(
+                        "{} | {} TICKS | {} COLLISIONS",
+                        record.level_code.to_uppercase(),
+                        record.last_tick,
+                        record.collisions.len()
+                    );
+                    if record.stride > 1 {
+                        header = format!("{} | SAMPLED EVERY {} TICKS", header, record.stride);
+                    }
+                    parent.spawn(TextBundle::from_section(
+                        header,
+                        TextStyle {
+                            font: asset_server.load(theme::FONT),
+                            font_size: GRAPH_FS,
+                            color: theme::SECONDARY_COLOR,
+                        },
+                    ));
+                    for (slot, index) in record.balls.iter().enumerate() {
+                        parent.spawn(TextBundle::from_section(
+                            format!("#{}", index),
+                            TextStyle {
+                                font: asset_server.load(theme::FONT),
+                                font_size: GRAPH_FS,
+                                color: GRAPH_COLORS[slot % GRAPH_COLORS.len()],
+                            },
+                        ));
+                    }
+                });
+            ui::build_icon_btn(
+                parent,
+                &asset_server,
+                (
+                    ButtonAction::MoveToPage(PageState::Level),
+                    app::interaction::IaButton,
+                    Focusable::new().prioritized(),
+                ),
+                Style {
+                    position_type: PositionType::Absolute,
+                    bottom: ui::px_p(ui::PAGE_PADDING),
+                    left: ui::px_p(ui::PAGE_PADDING),
+                    ..default()
+                },
+                "arrow-left-light_1.5x",
+            );
+        });
+}
+
+// NOTE: The graphs are lyon paths in the world above the game cover, one panel per quantity with
+// the collisions marked as vertical lines
+fn build_graphs(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    window_query: Query<&Window>,
+    record: Res<KinematicsRecord>,
+) {
+    let window = window_query.single();
+    let win_w = window.resolution.width();
+    let win_h = window.resolution.height();
+    commands
+        .spawn((
+            SpatialBundle {
+                transform: Transform::from_xyz(0.0, 0.0, layer::PAGE_GRAPH_Z_INDEX),
+                ..default()
+            },
+            OnPage,
+        ))
+        .with_children(|parent| {
+            let cover = shapes::Rectangle {
+                extents: Vec2::new(win_w, win_h),
+                ..default()
+            };
+            parent.spawn((
+                ShapeBundle {
+                    path: GeometryBuilder::build_as(&cover),
+                    ..default()
+                },
+                Fill::color(theme::BG_COLOR),
+            ));
+            if record.is_empty() {
+                return;
+            }
+            let top = win_h / 2.0 - win_h * GRAPH_TOP_RATIO;
+            let bottom = -win_h / 2.0 + win_h * GRAPH_BOTTOM_RATIO;
+            let rows = KinematicsQuantity::ALL.len().div_ceil(GRAPH_COLUMNS);
+            let cell_w = (win_w - GRAPH_GAP * (GRAPH_COLUMNS + 1) as f32) / GRAPH_COLUMNS as f32;
+            let cell_h = (top - bottom - GRAPH_GAP * (rows - 1) as f32) / rows as f32;
+            for (i, quantity) in KinematicsQuantity::ALL.iter().enumerate() {
+                let column = i % GRAPH_COLUMNS;
+                let row = i / GRAPH_COLUMNS;
+                let min = Vec2::new(
+                    -win_w / 2.0 + GRAPH_GAP + (cell_w + GRAPH_GAP) * column as f32,
+                    top - (cell_h + GRAPH_GAP) * row as f32 - cell_h,
+                );
+                build_graph(
+                    parent,
+                    &asset_server,
+                    &record,
+                    *quantity,
+                    Rect::from_corners(min, min + Vec2::new(cell_w, cell_h)),
+                );
+            }
+        });
+}
+
+fn build_graph(
+    parent: &mut ChildBuilder,
+    asset_server: &Res<AssetServer>,
+    record: &KinematicsRecord,
+    quantity: KinematicsQuantity,
+    rect: Rect,
+) {
+    let series = record.series(quantity);
+    let first_tick = record.samples.first().map_or(0, |sample| sample.tick);
+    let last_tick = record.samples.last().map_or(0, |sample| sample.tick);
+    let tick_span = (last_tick - first_tick).max(1) as f32;
+    let (mut low, mut high) = series
+        .iter()
+        .flatten()
+        .fold((f32::MAX, f32::MIN), |(low, high), (_, value)| {
+            (low.min(*value), high.max(*value))
+        });
+    if low > high {
+        (low, high) = (0.0, 0.0);
+    }
+    if (high - low).abs() < f32::EPSILON {
+        low -= 1.0;
+        high += 1.0;
+    }
+    let to_point = |tick: u32, value: f32| {
+        Vec2::new(
+            rect.min.x + rect.width() * (tick - first_tick) as f32 / tick_span,
+            rect.min.y + rect.height() * (value - low) / (high - low),
+        )
+    };
+    let frame = shapes::Rectangle {
+        extents: rect.size(),
+        origin: RectangleOrigin::CustomCenter(rect.center()),
+    };
+    parent.spawn((
+        ShapeBundle {
+            path: GeometryBuilder::build_as(&frame),
+            spatial: SpatialBundle {
+                transform: Transform::from_xyz(0.0, 0.0, 0.001),
+                ..default()
+            },
+            ..default()
+        },
+        Fill::color(theme::DARK_BG_COLOR),
+        Stroke::new(theme::MUTE_COLOR, GRAPH_FRAME_W),
+    ));
+    let mut axis_builder = PathBuilder::new();
+    if low < 0.0 && high > 0.0 {
+        axis_builder.move_to(to_point(first_tick, 0.0));
+        axis_builder.line_to(to_point(last_tick, 0.0));
+    }
+    let mut marker_builder = PathBuilder::new();
+    for tick in record.collisions.iter() {
+        let x = to_point(*tick, low).x;
+        marker_builder.move_to(Vec2::new(x, rect.min.y));
+        marker_builder.line_to(Vec2::new(x, rect.max.y));
+    }
+    for (builder, color) in [
+        (axis_builder, theme::MUTE_COLOR),
+        (marker_builder, theme::FAILURE_COLOR.with_alpha(0.5)),
+    ] {
+        parent.spawn((
+            ShapeBundle {
+                path: builder.build(),
+                spatial: SpatialBundle {
+                    transform: Transform::from_xyz(0.0, 0.0, 0.002),
+                    ..default()
+                },
+                ..default()
+            },
+            Stroke::new(color, GRAPH_FRAME_W),
+        ));
+    }
+    for (slot, points) in series.iter().enumerate() {
+        let mut line_builder = PathBuilder::new();
+        for (i, (tick, value)) in points.iter().enumerate() {
+            if i == 0 {
+                line_builder.move_to(to_point(*tick, *value));
+            } else {
+                line_builder.line_to(to_point(*tick, *value));
+            }
+        }
+        let color = if quantity.is_total() {
+            theme::FG_COLOR
+        } else {
+            GRAPH_COLORS[slot % GRAPH_COLORS.len()]
+        };
+        parent.spawn((
+            ShapeBundle {
+                path: line_builder.build(),
+                spatial: SpatialBundle {
+                    transform: Transform::from_xyz(0.0, 0.0, 0.003),
+                    ..default()
+                },
+                ..default()
+            },
+            Stroke::new(color, GRAPH_LINE_W),
+        ));
+    }
+    let inner = rect.inflate(-GRAPH_LABEL_PADDING);
+    let labels = [
+        (
+            quantity.label().to_uppercase(),
+            Vec2::new(inner.min.x, inner.max.y),
+            bevy::sprite::Anchor::TopLeft,
+        ),
+        (
+            format!("{:.2}", high),
+            inner.max,
+            bevy::sprite::Anchor::TopRight,
+        ),
+        (
+            format!("{:.2}", low),
+            Vec2::new(inner.max.x, inner.min.y),
+            bevy::sprite::Anchor::BottomRight,
+        ),
+    ];
+    for (text, pos, anchor) in labels {
+        parent.spawn(Text2dBundle {
+            text: Text::from_section(
+                text,
+                TextStyle {
+                    font: asset_server.load(theme::FONT),
+                    font_size: GRAPH_FS,
+                    color: theme::SECONDARY_COLOR,
+                },
+            ),
+            text_anchor: anchor,
+            transform: Transform::from_translation(pos.extend(0.004)),
+            ..default()
+        });
+    }
+}
+
+fn handle_ui_navigation(
+    mut actions: Query<&mut ButtonAction>,
+    mut events: EventReader<NavEvent>,
+    mut page_state: ResMut<NextState<PageState>>,
+) {
+    events.nav_iter().activated_in_query_foreach_mut(
+        &mut actions,
+        |mut action| match &mut *action {
+            ButtonAction::MoveToPage(state) => page_state.set(*state),
+        },
+    );
+}
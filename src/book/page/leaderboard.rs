@@ -1,10 +1,10 @@
-use crate::{app::anime_effect, app::theme::*, app::ui, book::page::*};
+use crate::{app::anime_effect, app::theme::*, app::ui, book::page::*, game::*};
 use bevy_alt_ui_navigation_lite::{prelude::*, NavRequestSystem};
 use bevy_persistent::prelude::*;
 
 const PAGE_CODE: &str = "leaderboard";
 const PAGE_NAME: &str = "Report";
-const PAGE_ICON: &str = "list-numbers";
+const PAGE_ICON: &str = "gauge-fill";
 
 pub struct Page;
 
@@ -28,7 +28,11 @@ impl PageBase for Page {
         )
         .add_systems(
             Update,
-            (handle_ui_navigation, app::interaction::handle_default_focus)
+            (
+                handle_ui_navigation,
+                handle_replay_drop,
+                app::interaction::handle_default_focus,
+            )
                 .after(NavRequestSystem)
                 .run_if(in_state(self.state())),
         )
@@ -43,31 +47,43 @@ impl PageBase for Page {
 struct OnPage;
 
 #[derive(Component)]
-pub struct HelpPanel(u8);
+struct LevelTitle;
 
 #[derive(Component)]
-pub struct HelpDot(u8);
+struct HistoryList;
+
+#[derive(Component)]
+struct ReplayMessage;
 
 #[derive(Component)]
 enum ButtonAction {
     BackToMainMenu,
-    PrevHelp,
-    NextHelp,
+    PrevLevel,
+    NextLevel,
+    Replay(LevelHistory),
+    Export(LevelHistory),
     Start,
 }
 
+const HISTORY_LIST_W: f32 = 720.0;
+const HISTORY_FS: f32 = ui::FONT_SIZE * 0.8;
+const DROP_HINT: &str = "DROP A REPLAY FILE HERE TO PLAY IT";
+
 fn page_enter(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
-    mut settings: ResMut<Persistent<app::settings::Settings>>,
+    level_assets: Res<Assets<LevelConfig>>,
+    catalog_assets: Res<Assets<LevelCatalog>>,
+    leaderboard: Res<Persistent<Leaderboard>>,
+    mut game_status: ResMut<GameStatus>,
 ) {
-    if settings.is_enabled("first") {
-        settings
-            .update(|settings| {
-                settings.toggle("first");
-            })
-            .expect("failed to update first run in help");
+    // NOTE: Show the records of the first level in the catalogue if no level is chosen
+    if game_status.level_code.is_empty() {
+        if let Some(level_code) = level_codes(&catalog_assets).first() {
+            game_status.level_code = level_code.clone();
+        }
     }
+    let level_code = game_status.level_code.clone();
     commands
         .spawn((build_page_layout(), OnPage))
         .with_children(|parent| {
@@ -101,7 +117,7 @@ fn page_enter(
                                 parent,
                                 &asset_server,
                                 (
-                                    ButtonAction::PrevHelp,
+                                    ButtonAction::PrevLevel,
                                     app::interaction::IaButton,
                                     Focusable::default(),
                                 ),
@@ -111,8 +127,9 @@ fn page_enter(
                             parent
                                 .spawn(NodeBundle {
                                     style: Style {
+                                        width: Val::Px(HISTORY_LIST_W),
                                         flex_direction: FlexDirection::Column,
-                                        align_items: AlignItems::Start,
+                                        align_items: AlignItems::Center,
                                         justify_content: JustifyContent::Center,
                                         margin: UiRect::all(ui::px_p(4.0)),
                                         row_gap: ui::px_p(4.0),
@@ -121,14 +138,55 @@ fn page_enter(
                                     ..default()
                                 })
                                 .with_children(|parent| {
-                                    build_help_panel(parent, &asset_server);
-                                    build_help_dots(parent);
+                                    parent.spawn((
+                                        TextBundle::from_section(
+                                            level_title(&level_assets, &level_code),
+                                            TextStyle {
+                                                font: asset_server.load(FONT_TITLE),
+                                                font_size: ui::FONT_SIZE * 1.2,
+                                                color: FG_COLOR,
+                                            },
+                                        ),
+                                        LevelTitle,
+                                    ));
+                                    parent
+                                        .spawn((
+                                            NodeBundle {
+                                                style: Style {
+                                                    width: Val::Percent(100.0),
+                                                    flex_direction: FlexDirection::Column,
+                                                    align_items: AlignItems::Stretch,
+                                                    row_gap: ui::px_p(2.0),
+                                                    ..default()
+                                                },
+                                                ..default()
+                                            },
+                                            HistoryList,
+                                        ))
+                                        .with_children(|parent| {
+                                            build_history_rows(
+                                                parent,
+                                                &asset_server,
+                                                &leaderboard.level_info(level_code.clone()),
+                                            );
+                                        });
+                                    parent.spawn((
+                                        TextBundle::from_section(
+                                            DROP_HINT,
+                                            TextStyle {
+                                                font: asset_server.load(FONT),
+                                                font_size: HISTORY_FS * 0.8,
+                                                color: SECONDARY_COLOR,
+                                            },
+                                        ),
+                                        ReplayMessage,
+                                    ));
                                 });
                             ui::build_icon_btn(
                                 parent,
                                 &asset_server,
                                 (
-                                    ButtonAction::NextHelp,
+                                    ButtonAction::NextLevel,
                                     app::interaction::IaButton,
                                     Focusable::default(),
                                     app::interaction::IaDefaultFocus,
@@ -175,117 +233,193 @@ fn page_enter(
         });
 }
 
+#[allow(clippy::too_many_arguments)]
 fn handle_ui_navigation(
+    mut commands: Commands,
     mut actions: Query<&mut ButtonAction>,
     mut events: EventReader<NavEvent>,
     mut page_state: ResMut<NextState<PageState>>,
-    mut help_panel_query: Query<(&mut HelpPanel, &mut UiImage), With<HelpPanel>>,
-    mut help_dot_query: Query<(&HelpDot, &mut BackgroundColor), With<HelpDot>>,
+    mut title_query: Query<&mut Text, (With<LevelTitle>, Without<ReplayMessage>)>,
+    mut message_query: Query<&mut Text, (With<ReplayMessage>, Without<LevelTitle>)>,
+    list_query: Query<Entity, With<HistoryList>>,
     asset_server: Res<AssetServer>,
+    level_assets: Res<Assets<LevelConfig>>,
+    catalog_assets: Res<Assets<LevelCatalog>>,
+    leaderboard: Res<Persistent<Leaderboard>>,
+    mut game_status: ResMut<GameStatus>,
 ) {
+    let mut level_shift = 0;
     events.nav_iter().activated_in_query_foreach_mut(
         &mut actions,
         |mut action| match &mut *action {
             ButtonAction::BackToMainMenu => page_state.set(PageState::Menu),
-            ButtonAction::PrevHelp => {
-                let (mut help_panel, mut image) = help_panel_query.single_mut();
-                let prev_help = (help_panel.0 + HELP_COUNT - 1) % HELP_COUNT;
-                help_panel.0 = prev_help;
-                let icon_path = format!("images/help/{:0>2}.png", prev_help);
-                let icon = asset_server.load(icon_path);
-                image.texture = icon;
-                for (help_dot, mut bg_color) in help_dot_query.iter_mut() {
-                    if help_dot.0 == prev_help {
-                        *bg_color = MUTE_COLOR.into();
-                    } else {
-                        *bg_color = BG_COLOR.into();
-                    }
-                }
+            ButtonAction::PrevLevel => level_shift = -1,
+            ButtonAction::NextLevel => level_shift = 1,
+            ButtonAction::Replay(history) => {
+                game_status.replay = Some(ReplayStatus::new(history.clone()));
+                page_state.set(PageState::Game);
             }
-            ButtonAction::NextHelp => {
-                let (mut help_panel, mut image) = help_panel_query.single_mut();
-                let next_help = (help_panel.0 + 1) % HELP_COUNT;
-                help_panel.0 = next_help;
-                let icon_path = format!("images/help/{:0>2}.png", next_help);
-                let icon = asset_server.load(icon_path);
-                image.texture = icon;
-                for (help_dot, mut bg_color) in help_dot_query.iter_mut() {
-                    if help_dot.0 == next_help {
-                        *bg_color = MUTE_COLOR.into();
-                    } else {
-                        *bg_color = BG_COLOR.into();
-                    }
+            ButtonAction::Export(history) => {
+                let message = match fetch_level(&level_assets, &game_status.level_code) {
+                    Some((_, level_config)) => match export_replay(level_config, history) {
+                        Ok(path) => format!("EXPORTED TO {}", path.display()),
+                        Err(error) => error.to_string().to_uppercase(),
+                    },
+                    None => format!(
+                        "THE LEVEL {} IS NOT INSTALLED",
+                        game_status.level_code.to_uppercase()
+                    ),
+                };
+                if let Ok(mut text) = message_query.get_single_mut() {
+                    text.sections[0].value = message;
                 }
             }
             ButtonAction::Start => page_state.set(PageState::Game),
         },
     );
+    if level_shift == 0 {
+        return;
+    }
+    let codes = level_codes(&catalog_assets);
+    if codes.is_empty() {
+        return;
+    }
+    let current = codes
+        .iter()
+        .position(|code| *code == game_status.level_code)
+        .unwrap_or(0) as i32;
+    let next = (current + level_shift).rem_euclid(codes.len() as i32) as usize;
+    game_status.level_code = codes[next].clone();
+    if let Ok(mut text) = title_query.get_single_mut() {
+        text.sections[0].value = level_title(&level_assets, &game_status.level_code);
+    }
+    if let Ok(list_entity) = list_query.get_single() {
+        let mut entity_commands = commands.entity(list_entity);
+        entity_commands.despawn_descendants();
+        entity_commands.with_children(|parent| {
+            build_history_rows(
+                parent,
+                &asset_server,
+                &leaderboard.level_info(game_status.level_code.clone()),
+            );
+        });
+    }
 }
 
-const HELP_PANEL_SIZE: f32 = 480.0;
-const HELP_COUNT: u8 = 14;
+fn level_title(level_assets: &Assets<LevelConfig>, level_code: &str) -> String {
+    if let Some((_, level_config)) = fetch_level(level_assets, level_code) {
+        level_config.name.clone()
+    } else {
+        level_code.to_uppercase()
+    }
+}
 
-fn build_help_panel(parent: &mut ChildBuilder, asset_server: &Res<AssetServer>) {
-    parent
-        .spawn((NodeBundle {
-            style: Style {
-                width: Val::Px(HELP_PANEL_SIZE),
-                height: Val::Px(HELP_PANEL_SIZE),
-                align_items: AlignItems::Center,
-                justify_content: JustifyContent::Center,
-                border: UiRect::all(ui::px_p(0.5)),
+fn build_history_rows(
+    parent: &mut ChildBuilder,
+    asset_server: &Res<AssetServer>,
+    record: &LevelRecord,
+) {
+    if record.best_history.is_none() && record.histories.is_empty() {
+        parent.spawn(
+            TextBundle::from_section(
+                "NO RECORDS",
+                TextStyle {
+                    font: asset_server.load(FONT),
+                    font_size: HISTORY_FS,
+                    color: SECONDARY_COLOR,
+                },
+            )
+            .with_style(Style {
+                align_self: AlignSelf::Center,
                 ..default()
-            },
-            background_color: BG_COLOR.into(),
-            border_color: MUTE_COLOR.into(),
-            ..default()
-        },))
-        .with_children(|parent| {
-            let icon = asset_server.load("images/help/00.png");
-            parent.spawn((
-                ImageBundle {
-                    image: UiImage::new(icon),
+            }),
+        );
+        return;
+    }
+    let rows = record
+        .best_history
+        .iter()
+        .map(|history| ("BEST", history))
+        .chain(record.histories.iter().rev().map(|history| ("", history)));
+    for (label, history) in rows {
+        let text = format!(
+            "{:<4} {:<5} {:>5}  {}",
+            label,
+            if history.is_clear { "CLEAR" } else { "FAIL" },
+            history.time,
+            history
+                .created_at
+                .replace('_', " ")
+                .get(..16)
+                .unwrap_or_default(),
+        );
+        parent
+            .spawn(NodeBundle {
+                style: Style {
+                    align_items: AlignItems::Center,
+                    column_gap: ui::px_p(2.0),
                     ..default()
                 },
-                HelpPanel(0),
-            ));
-        });
-}
-
-const HELP_DOT_SIZE: f32 = 5.0;
-
-fn build_help_dots(parent: &mut ChildBuilder) {
-    parent
-        .spawn((NodeBundle {
-            style: Style {
-                width: Val::Percent(100.0),
-                align_items: AlignItems::Center,
-                justify_content: JustifyContent::Center,
-                column_gap: ui::px_p(3.0),
                 ..default()
-            },
-            ..default()
-        },))
-        .with_children(|parent| {
-            for i in 0..HELP_COUNT {
-                let mut bg_color = BG_COLOR.into();
-                if i == 0 {
-                    bg_color = MUTE_COLOR.into();
-                }
-                parent.spawn((
-                    NodeBundle {
-                        style: Style {
-                            width: ui::px_p(HELP_DOT_SIZE),
-                            height: ui::px_p(HELP_DOT_SIZE),
-                            border: UiRect::all(ui::px_p(0.5)),
-                            ..default()
-                        },
-                        background_color: bg_color,
-                        border_color: MUTE_COLOR.into(),
+            })
+            .with_children(|parent| {
+                ui::build_btn(
+                    parent,
+                    asset_server,
+                    (
+                        ButtonAction::Replay(history.clone()),
+                        app::interaction::IaButton,
+                        Focusable::default(),
+                    ),
+                    Style {
+                        flex_grow: 1.0,
+                        padding: UiRect::all(ui::px_p(ui::BTN_PADDING)),
                         ..default()
                     },
-                    HelpDot(i),
-                ));
+                    Some(&text),
+                    Some("play-light"),
+                );
+                ui::build_icon_btn(
+                    parent,
+                    asset_server,
+                    (
+                        ButtonAction::Export(history.clone()),
+                        app::interaction::IaButton,
+                        Focusable::default(),
+                    ),
+                    Style::default(),
+                    "arrow-fat-up-fill",
+                );
+            });
+    }
+}
+
+// NOTE: Shared replays are imported by dropping the file on the window
+fn handle_replay_drop(
+    mut drop_events: EventReader<FileDragAndDrop>,
+    mut message_query: Query<&mut Text, With<ReplayMessage>>,
+    mut page_state: ResMut<NextState<PageState>>,
+    level_assets: Res<Assets<LevelConfig>>,
+    mut game_status: ResMut<GameStatus>,
+) {
+    for event in drop_events.read() {
+        let FileDragAndDrop::DroppedFile { path_buf, .. } = event else {
+            continue;
+        };
+        if !path_buf.to_string_lossy().ends_with(REPLAY_EXT) {
+            continue;
+        }
+        match import_replay(path_buf, &level_assets) {
+            Ok(replay_file) => {
+                game_status.level_code = replay_file.level;
+                game_status.replay = Some(ReplayStatus::new(replay_file.history));
+                page_state.set(PageState::Game);
             }
-        });
+            Err(error) => {
+                if let Ok(mut text) = message_query.get_single_mut() {
+                    text.sections[0].value = error.to_string().to_uppercase();
+                }
+            }
+        }
+    }
 }
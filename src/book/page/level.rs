@@ -1,4 +1,4 @@
-use crate::{app::anime_effect, app::theme::*, app::ui, book::page::*};
+use crate::{app::anime_effect, app::theme::*, app::ui, book::page::*, game::*};
 use bevy_alt_ui_navigation_lite::{prelude::*, NavRequestSystem};
 use bevy_persistent::prelude::*;
 
@@ -42,21 +42,43 @@ impl PageBase for Page {
 #[derive(Component)]
 struct OnPage;
 
-#[derive(Component)]
-pub struct HelpPanel(u8);
-
-#[derive(Component)]
-pub struct HelpDot(u8);
-
 #[derive(Component)]
 enum ButtonAction {
     BackToMainMenu,
-    PrevHelp,
-    NextHelp,
-    Start,
+    StartLevel(String),
+}
+
+const LEVEL_GRID_COLUMNS: u16 = 4;
+const LEVEL_ENTRY_W: f32 = ui::FONT_SIZE * 6.0;
+const LEVEL_NAME_FS: f32 = ui::FONT_SIZE * 0.8;
+const LEVEL_INFO_FS: f32 = ui::FONT_SIZE * 0.5;
+const LEVEL_STAR_SIZE: f32 = LEVEL_INFO_FS;
+
+struct LevelEntry {
+    code: String,
+    name: String,
+    is_clear: bool,
+    best_time: Option<u32>,
+    best_stars: u8,
+    required_stars: u32,
+    is_unlocked: bool,
 }
 
-fn page_enter(mut commands: Commands, asset_server: Res<AssetServer>) {
+fn page_enter(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    level_assets: Res<Assets<LevelConfig>>,
+    catalog_assets: Res<Assets<LevelCatalog>>,
+    leaderboard: Res<Persistent<Leaderboard>>,
+    game_status: Res<GameStatus>,
+) {
+    let entries = build_level_entries(&level_assets, &catalog_assets, &leaderboard);
+    // NOTE: Focus on the chosen level, or the first one if it is not available
+    let focus_code = entries
+        .iter()
+        .find(|entry| entry.is_unlocked && entry.code == game_status.level_code)
+        .or(entries.first())
+        .map(|entry| entry.code.clone());
     commands
         .spawn((build_page_layout(), OnPage))
         .with_children(|parent| {
@@ -79,52 +101,47 @@ fn page_enter(mut commands: Commands, asset_server: Res<AssetServer>) {
                             style: Style {
                                 width: Val::Percent(100.0),
                                 flex_grow: 1.0,
+                                flex_direction: FlexDirection::Column,
                                 align_items: AlignItems::Center,
                                 justify_content: JustifyContent::Center,
+                                row_gap: ui::px_p(4.0),
                                 ..default()
                             },
                             ..default()
                         })
                         .with_children(|parent| {
-                            ui::build_icon_btn(
-                                parent,
-                                &asset_server,
-                                (
-                                    ButtonAction::PrevHelp,
-                                    app::interaction::IaButton,
-                                    Focusable::default(),
+                            parent.spawn(TextBundle::from_section(
+                                format!(
+                                    "STARS {} / {}",
+                                    leaderboard.total_stars(),
+                                    entries.len() as u32 * MAX_STARS as u32
                                 ),
-                                Style::default(),
-                                "caret-double-left-light",
-                            );
+                                TextStyle {
+                                    font: asset_server.load(FONT),
+                                    font_size: LEVEL_NAME_FS,
+                                    color: SECONDARY_COLOR,
+                                },
+                            ));
                             parent
                                 .spawn(NodeBundle {
                                     style: Style {
-                                        flex_direction: FlexDirection::Column,
-                                        align_items: AlignItems::Start,
-                                        justify_content: JustifyContent::Center,
-                                        margin: UiRect::all(ui::px_p(4.0)),
-                                        row_gap: ui::px_p(4.0),
+                                        display: Display::Grid,
+                                        grid_template_columns: vec![
+                                            GridTrack::px(LEVEL_ENTRY_W);
+                                            LEVEL_GRID_COLUMNS as usize
+                                        ],
+                                        column_gap: ui::px_p(ui::MENU_ENTRY_PADDING),
+                                        row_gap: ui::px_p(ui::MENU_ENTRY_PADDING),
                                         ..default()
                                     },
                                     ..default()
                                 })
                                 .with_children(|parent| {
-                                    build_help_panel(parent, &asset_server);
-                                    build_help_dots(parent);
+                                    for entry in entries.iter() {
+                                        let is_current = Some(&entry.code) == focus_code.as_ref();
+                                        build_level_entry(parent, &asset_server, entry, is_current);
+                                    }
                                 });
-                            ui::build_icon_btn(
-                                parent,
-                                &asset_server,
-                                (
-                                    ButtonAction::NextHelp,
-                                    app::interaction::IaButton,
-                                    Focusable::default(),
-                                    app::interaction::IaDefaultFocus,
-                                ),
-                                Style::default(),
-                                "caret-double-right-light",
-                            );
                         });
                 });
             ui::build_icon_btn(
@@ -143,24 +160,6 @@ fn page_enter(mut commands: Commands, asset_server: Res<AssetServer>) {
                 },
                 "arrow-left-light_1.5x",
             );
-            ui::build_btn(
-                parent,
-                &asset_server,
-                (
-                    ButtonAction::Start,
-                    app::interaction::IaButton,
-                    Focusable::default(),
-                ),
-                Style {
-                    position_type: PositionType::Absolute,
-                    bottom: ui::px_p(ui::PAGE_PADDING),
-                    right: ui::px_p(ui::PAGE_PADDING),
-                    padding: UiRect::all(ui::px_p(ui::BTN_PADDING)),
-                    ..default()
-                },
-                Some("START"),
-                Some("play-light"),
-            );
         });
 }
 
@@ -168,111 +167,125 @@ fn handle_ui_navigation(
     mut actions: Query<&mut ButtonAction>,
     mut events: EventReader<NavEvent>,
     mut page_state: ResMut<NextState<PageState>>,
-    mut help_panel_query: Query<(&mut HelpPanel, &mut UiImage), With<HelpPanel>>,
-    mut help_dot_query: Query<(&HelpDot, &mut BackgroundColor), With<HelpDot>>,
-    asset_server: Res<AssetServer>,
+    mut game_status: ResMut<GameStatus>,
 ) {
     events.nav_iter().activated_in_query_foreach_mut(
         &mut actions,
         |mut action| match &mut *action {
             ButtonAction::BackToMainMenu => page_state.set(PageState::Menu),
-            ButtonAction::PrevHelp => {
-                let (mut help_panel, mut image) = help_panel_query.single_mut();
-                let prev_help = (help_panel.0 + HELP_COUNT - 1) % HELP_COUNT;
-                help_panel.0 = prev_help;
-                let icon_path = format!("images/help/{:0>2}.png", prev_help);
-                let icon = asset_server.load(icon_path);
-                image.texture = icon;
-                for (help_dot, mut bg_color) in help_dot_query.iter_mut() {
-                    if help_dot.0 == prev_help {
-                        *bg_color = MUTE_COLOR.into();
-                    } else {
-                        *bg_color = BG_COLOR.into();
-                    }
-                }
+            ButtonAction::StartLevel(level_code) => {
+                game_status.level_code = level_code.clone();
+                page_state.set(PageState::Game);
             }
-            ButtonAction::NextHelp => {
-                let (mut help_panel, mut image) = help_panel_query.single_mut();
-                let next_help = (help_panel.0 + 1) % HELP_COUNT;
-                help_panel.0 = next_help;
-                let icon_path = format!("images/help/{:0>2}.png", next_help);
-                let icon = asset_server.load(icon_path);
-                image.texture = icon;
-                for (help_dot, mut bg_color) in help_dot_query.iter_mut() {
-                    if help_dot.0 == next_help {
-                        *bg_color = MUTE_COLOR.into();
-                    } else {
-                        *bg_color = BG_COLOR.into();
-                    }
-                }
-            }
-            ButtonAction::Start => page_state.set(PageState::Game),
         },
     );
 }
 
-const HELP_PANEL_SIZE: f32 = 480.0;
-const HELP_COUNT: u8 = 9;
-
-fn build_help_panel(parent: &mut ChildBuilder, asset_server: &Res<AssetServer>) {
-    parent
-        .spawn((NodeBundle {
-            style: Style {
-                width: Val::Px(HELP_PANEL_SIZE),
-                height: Val::Px(HELP_PANEL_SIZE),
-                align_items: AlignItems::Center,
-                justify_content: JustifyContent::Center,
-                ..default()
-            },
-            ..default()
-        },))
-        .with_children(|parent| {
-            let icon = asset_server.load("images/help/00.png");
-            parent.spawn((
-                ImageBundle {
-                    image: UiImage::new(icon),
-                    ..default()
-                },
-                HelpPanel(0),
-            ));
-        });
+fn build_level_entries(
+    level_assets: &Assets<LevelConfig>,
+    catalog_assets: &Assets<LevelCatalog>,
+    leaderboard: &Leaderboard,
+) -> Vec<LevelEntry> {
+    let Some(catalog) = fetch_catalog(catalog_assets) else {
+        return vec![];
+    };
+    let is_clear = |code: &str| leaderboard.level_info(String::from(code)).is_clear;
+    let total_stars = leaderboard.total_stars();
+    catalog
+        .levels
+        .iter()
+        .enumerate()
+        .map(|(index, code)| {
+            let record = leaderboard.level_info(code.clone());
+            LevelEntry {
+                code: code.clone(),
+                name: fetch_level(level_assets, code)
+                    .map(|(_, level_config)| level_config.name.clone())
+                    .unwrap_or_else(|| code.to_uppercase()),
+                is_clear: record.is_clear,
+                best_time: record.best_history.map(|history| history.time),
+                best_stars: record.stars,
+                required_stars: catalog.required_stars(index),
+                is_unlocked: catalog.is_unlocked(index, total_stars, is_clear),
+            }
+        })
+        .collect()
 }
 
-const HELP_DOT_SIZE: f32 = 5.0;
-
-fn build_help_dots(parent: &mut ChildBuilder) {
-    parent
-        .spawn((NodeBundle {
-            style: Style {
-                width: Val::Percent(100.0),
-                align_items: AlignItems::Center,
-                justify_content: JustifyContent::Center,
-                column_gap: ui::px_p(3.0),
-                ..default()
-            },
+fn build_level_entry(
+    parent: &mut ChildBuilder,
+    asset_server: &Res<AssetServer>,
+    entry: &LevelEntry,
+    is_current: bool,
+) {
+    let (status, status_color) = if !entry.is_unlocked {
+        if entry.required_stars > 0 {
+            (format!("NEED {} STARS", entry.required_stars), MUTE_COLOR)
+        } else {
+            (String::from("LOCKED"), MUTE_COLOR)
+        }
+    } else if entry.is_clear {
+        (String::from("CLEAR"), SUCCESS_COLOR)
+    } else {
+        (String::from("NEW"), SECONDARY_COLOR)
+    };
+    let best_time = entry
+        .best_time
+        .map_or(String::from("---"), |time| format!("{:0>4}", time));
+    let name_color = if entry.is_unlocked {
+        FG_COLOR
+    } else {
+        MUTE_COLOR
+    };
+    let mut entity_commands = parent.spawn(ButtonBundle {
+        style: Style {
+            flex_direction: FlexDirection::Column,
+            align_items: AlignItems::Center,
+            padding: UiRect::all(ui::px_p(ui::BTN_PADDING * 2.0)),
+            row_gap: ui::px_p(1.0),
+            border: UiRect::all(ui::px_p(0.5)),
             ..default()
-        },))
-        .with_children(|parent| {
-            for i in 0..HELP_COUNT {
-                let mut bg_color = BG_COLOR.into();
-                if i == 0 {
-                    bg_color = MUTE_COLOR.into();
-                }
-                parent.spawn((
-                    NodeBundle {
-                        style: Style {
-                            width: ui::px_p(HELP_DOT_SIZE),
-                            height: ui::px_p(HELP_DOT_SIZE),
-                            border: UiRect::all(ui::px_p(0.5)),
-                            ..default()
-                        },
-                        background_color: bg_color,
-                        border_color: MUTE_COLOR.into(),
-                        border_radius: BorderRadius::MAX,
-                        ..default()
-                    },
-                    HelpDot(i),
-                ));
-            }
-        });
+        },
+        background_color: BTN_BG.into(),
+        border_color: MUTE_COLOR.into(),
+        ..default()
+    });
+    // NOTE: Locked levels are shown but can not be focused or selected
+    if entry.is_unlocked {
+        entity_commands.insert((
+            ButtonAction::StartLevel(entry.code.clone()),
+            app::interaction::IaButton,
+            Focusable::default(),
+        ));
+        if is_current {
+            entity_commands.insert(app::interaction::IaDefaultFocus);
+        }
+    }
+    entity_commands.with_children(|parent| {
+        parent.spawn(TextBundle::from_section(
+            entry.name.clone(),
+            TextStyle {
+                font: asset_server.load(FONT),
+                font_size: LEVEL_NAME_FS,
+                color: name_color,
+            },
+        ));
+        parent.spawn(TextBundle::from_section(
+            status,
+            TextStyle {
+                font: asset_server.load(FONT),
+                font_size: LEVEL_INFO_FS,
+                color: status_color,
+            },
+        ));
+        build_stars(parent, asset_server, entry.best_stars, LEVEL_STAR_SIZE);
+        parent.spawn(TextBundle::from_section(
+            best_time,
+            TextStyle {
+                font: asset_server.load(FONT_DIGIT),
+                font_size: LEVEL_INFO_FS,
+                color: name_color,
+            },
+        ));
+    });
 }
@@ -63,9 +63,11 @@ enum ButtonAction {
     Quit,
 }
 
-const MENU_PAGES: [&dyn PageBase; 4] = [
+const MENU_PAGES: [&dyn PageBase; 6] = [
     &help::Page,
     &level::Page,
+    &leaderboard::Page,
+    &editor::Page,
     &settings::audio::Page,
     &about::main::Page,
 ];
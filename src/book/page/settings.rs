@@ -1,4 +1,9 @@
-use crate::{app::theme, app::ui, book::page::*};
+use crate::{
+    app::theme,
+    app::ui,
+    book::page::*,
+    game::{is_in_pause_settings, GameStatus},
+};
 use bevy_alt_ui_navigation_lite::prelude::*;
 
 pub mod audio;
@@ -12,10 +17,20 @@ pub enum ButtonAction {
     PlaySe,
 }
 
+// NOTE: The settings opened from the pause go back to the paused level
+pub fn settings_back_page(game_status: &GameStatus) -> PageState {
+    if game_status.is_in_pause_settings {
+        PageState::Game
+    } else {
+        PageState::Menu
+    }
+}
+
 pub fn build_settings_nav_bar(
     parent: &mut ChildBuilder,
     asset_server: &Res<AssetServer>,
     page: PageState,
+    back_page: PageState,
 ) -> Entity {
     parent
         .spawn(NodeBundle {
@@ -35,7 +50,7 @@ pub fn build_settings_nav_bar(
                 parent,
                 &asset_server,
                 (
-                    ButtonAction::MoveToPage(PageState::Menu),
+                    ButtonAction::MoveToPage(back_page),
                     app::interaction::IaButton,
                     Focusable::default(),
                     app::interaction::IaDefaultFocus,
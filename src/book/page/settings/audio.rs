@@ -41,7 +41,8 @@ impl PageBase for Page {
         .add_systems(
             OnExit(self.state()),
             (
-                anime_effect::clear_anime_effect,
+                anime_effect::clear_anime_effect.run_if(not(is_in_pause_settings)),
+                interaction::clear_focus_effect.run_if(is_in_pause_settings),
                 element::clear_elements,
                 ui::despawn_ui::<OnPage>,
             ),
@@ -56,6 +57,7 @@ fn page_enter(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
     settings: Res<Persistent<app::settings::Settings>>,
+    game_status: Res<GameStatus>,
 ) {
     commands
         .spawn((build_page_layout(), OnPage))
@@ -163,7 +165,12 @@ fn page_enter(
                                     );
                                 });
                         });
-                    build_settings_nav_bar(parent, &asset_server, PageState::SettingsAudio);
+                    build_settings_nav_bar(
+                        parent,
+                        &asset_server,
+                        PageState::SettingsAudio,
+                        settings_back_page(&game_status),
+                    );
                 });
         });
 }
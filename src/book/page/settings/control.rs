@@ -44,7 +44,8 @@ impl PageBase for Page {
         .add_systems(
             OnExit(self.state()),
             (
-                anime_effect::clear_anime_effect,
+                anime_effect::clear_anime_effect.run_if(not(is_in_pause_settings)),
+                interaction::clear_focus_effect.run_if(is_in_pause_settings),
                 element::clear_elements,
                 ui::despawn_ui::<OnPage>,
                 ui::despawn_ui::<DemoPanel>,
@@ -81,6 +82,7 @@ fn page_enter(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
     settings: Res<Persistent<app::settings::Settings>>,
+    game_status: Res<GameStatus>,
 ) {
     commands
         .spawn((build_page_layout(), OnPage, Pickable::IGNORE))
@@ -226,8 +228,26 @@ fn page_enter(
                                     },
                                 },
                             );
+                            build_sep_title(parent, &asset_server, "Ghost", "shapes-fill");
+                            element::build_element(
+                                parent,
+                                &asset_server,
+                                ButtonAction::AppUiNav,
+                                element::ElementInitParams::Switcher {
+                                    data: element::ElementTargetValuePair {
+                                        target: String::from("ghost"),
+                                        bool_value: Some(settings.is_enabled("ghost")),
+                                        ..default()
+                                    },
+                                },
+                            );
                         });
-                    build_settings_nav_bar(parent, &asset_server, PageState::SettingsControl);
+                    build_settings_nav_bar(
+                        parent,
+                        &asset_server,
+                        PageState::SettingsControl,
+                        settings_back_page(&game_status),
+                    );
                 });
         });
 }
@@ -261,6 +281,13 @@ fn handle_element_events(
                         .expect("failed to update slider");
                     element::update_element_value(&mut ele_query, data.clone());
                 }
+                if data.bool_value.is_some() {
+                    settings
+                        .update(|settings| {
+                            settings.toggle(data.target.as_str());
+                        })
+                        .expect("failed to update switcher");
+                }
             }
             element::ElementEvent::Lock { entity: _ } => {
                 nav_requests.send(NavRequest::Lock);
@@ -1,22 +1,52 @@
 use bevy::prelude::*;
 
+mod aiming;
 mod anime_end;
 mod ball;
 mod ball_interaction;
+mod debug_overlay;
+mod editor;
 mod field;
+mod force_field;
+mod generator;
+mod ghost;
+mod kinematics;
 mod leaderboard;
 mod level_builder;
+mod par;
 mod phase;
+mod placing;
 mod plugin;
+mod replay;
+mod simulation;
+mod solver;
 mod startup;
 mod status;
+mod time_scale;
 mod timer;
+mod trajectory;
+mod wall;
 
 pub use ball::*;
+pub use debug_overlay::DebugOverlay;
+pub use editor::{
+    ball_type_at, handle_editing, handle_editor_keys, load_editor_level, new_level,
+    refresh_editor_view, save_editor_level, saved_editor_level_path, EditorStatus, EditorTextField,
+    EditorView, EDITOR_LEVEL_CODE,
+};
 pub use field::*;
+pub use generator::{generate_level, generated_dir_path, GeneratorConfig};
+pub use kinematics::{KinematicsQuantity, KinematicsRecord};
 pub use leaderboard::*;
+pub use level_builder::{
+    fetch_catalog, fetch_level, level_codes, LevelCatalog, LevelConfig, CATALOG_EXT, LEVEL_EXT,
+};
+pub use par::{ParConfig, MAX_STARS};
 pub use phase::PhaseState;
 pub use plugin::GamePlugin;
+pub use replay::{export_replay, import_replay, ReplayStatus, REPLAY_EXT};
+pub use solver::{solve, Solution, SolverConfig, SolverReport};
 pub use startup::*;
 pub use status::*;
+pub use time_scale::{TimeScale, TIME_SCALES};
 pub use timer::*;
@@ -0,0 +1,285 @@
+use super::{
+    level_builder::LevelConfig,
+    simulation::{ControlField, ControlValues, Simulation},
+    *,
+};
+use crate::app::{cursor::AppCursorData, settings, theme, ui};
+use bevy_persistent::prelude::*;
+use bevy_prototype_lyon::prelude::*;
+
+const MAX_DRAG_L: f32 = ui::FONT_SIZE * 8.0;
+const ANGLE_KEY_RATIO: f32 = 0.02;
+const FORCE_KEY_RATIO: f32 = 0.002;
+const AIM_GUIDE_RATIO: f32 = 6.0;
+const AIM_GUIDE_W: f32 = ui::FONT_SIZE / 12.0;
+const AIM_GUIDE_Z_LAYER: f32 = 0.9;
+
+#[derive(Component)]
+pub struct AimGuide;
+
+pub fn handle_aiming(
+    mouse_input: Res<ButtonInput<MouseButton>>,
+    key_input: Res<ButtonInput<KeyCode>>,
+    cursor_data: Res<AppCursorData>,
+    settings: Res<Persistent<settings::Settings>>,
+    mut game_status: ResMut<GameStatus>,
+    mut dragging: Local<Option<usize>>,
+) {
+    if game_status.mode != StatusMode::Deploying {
+        *dragging = None;
+        return;
+    }
+    if key_input.any_just_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]) {
+        game_status.in_modified_sensitivity = true;
+    }
+    if key_input.any_just_released([KeyCode::ShiftLeft, KeyCode::ShiftRight]) {
+        game_status.in_modified_sensitivity = false;
+    }
+    let level = game_status.current_level.clone();
+    let stones = launchable_stones(&level);
+    if stones.is_empty() {
+        if key_input.just_pressed(KeyCode::Space) {
+            launch(&mut game_status);
+        }
+        return;
+    }
+    let mut values = game_status.control_values.clone();
+    let mut aimed = game_status
+        .aimed_ball
+        .filter(|ball| stones.contains(ball))
+        .unwrap_or(stones[0]);
+    if key_input.just_pressed(KeyCode::Tab) && dragging.is_none() {
+        let index = stones.iter().position(|ball| *ball == aimed).unwrap_or(0);
+        aimed = stones[(index + 1) % stones.len()];
+    }
+    // NOTE: Drag backward from the stone like a slingshot, the picked stone is aimed from then on
+    if mouse_input.just_pressed(MouseButton::Left) {
+        let picked = stones
+            .iter()
+            .filter_map(|ball| {
+                let distance = cursor_data
+                    .canvas_pos
+                    .distance(values.position(&level, *ball)?);
+                (distance <= level.balls[*ball].property.radius * 2.0).then_some((*ball, distance))
+            })
+            .min_by(|(_, d1), (_, d2)| d1.total_cmp(d2));
+        if let Some((ball, _)) = picked {
+            aimed = ball;
+            *dragging = Some(ball);
+        }
+    }
+    if mouse_input.just_released(MouseButton::Left) {
+        *dragging = None;
+    }
+    if game_status.aimed_ball != Some(aimed) {
+        game_status.aimed_ball = Some(aimed);
+    }
+    let ball = aimed;
+    if let Some(stone_pos) = dragging.and_then(|ball| values.position(&level, ball)) {
+        let drag = stone_pos - cursor_data.canvas_pos;
+        if drag.length() > 0.0 {
+            let params = &level.balls[ball].control_params;
+            values.set(
+                &level,
+                ball,
+                ControlField::Angle,
+                drag.y.atan2(drag.x).to_degrees(),
+            );
+            if let Some((min, max)) = params.force {
+                let ratio = (drag.length() / MAX_DRAG_L).min(1.0);
+                values.set(&level, ball, ControlField::Force, min + (max - min) * ratio);
+            }
+        }
+    }
+    let sensitivity = if game_status.in_modified_sensitivity {
+        settings.get_value("sensitivity_modified")
+    } else {
+        settings.get_value("sensitivity")
+    } as f32;
+    let mut angle_delta = 0.0;
+    let mut force_delta = 0.0;
+    if key_input.just_pressed(KeyCode::ArrowLeft) {
+        angle_delta += sensitivity * ANGLE_KEY_RATIO;
+    }
+    if key_input.just_pressed(KeyCode::ArrowRight) {
+        angle_delta -= sensitivity * ANGLE_KEY_RATIO;
+    }
+    if key_input.just_pressed(KeyCode::ArrowUp) {
+        force_delta += sensitivity * FORCE_KEY_RATIO;
+    }
+    if key_input.just_pressed(KeyCode::ArrowDown) {
+        force_delta -= sensitivity * FORCE_KEY_RATIO;
+    }
+    if angle_delta != 0.0 {
+        if let Some(angle) = values.get(&level, ball, ControlField::Angle) {
+            values.set(&level, ball, ControlField::Angle, angle + angle_delta);
+        }
+    }
+    if force_delta != 0.0 {
+        if let Some(force) = values.get(&level, ball, ControlField::Force) {
+            values.set(&level, ball, ControlField::Force, force + force_delta);
+        }
+    }
+    if values != game_status.control_values {
+        game_status.control_values = values;
+    }
+    if key_input.just_pressed(KeyCode::Space) && dragging.is_none() {
+        launch(&mut game_status);
+    }
+}
+
+pub fn launch(game_status: &mut GameStatus) {
+    let values = game_status.control_values.clone();
+    game_status.simulation = Simulation::new(&game_status.current_level, &values);
+    game_status.simulation.start();
+    if let Some(ghost) = game_status.ghost.as_mut() {
+        ghost.start();
+    }
+    game_status.current_history.x = values.x;
+    game_status.current_history.y = values.y;
+    game_status.current_history.force = values.force;
+    game_status.current_history.angle = values.angle;
+    game_status.mode = StatusMode::Running;
+}
+
+pub fn refresh_control_displays(
+    mut game_status: ResMut<GameStatus>,
+    mut last_values: Local<Option<(String, ControlValues)>>,
+) {
+    let values_key = Some((
+        game_status.current_level.code.clone(),
+        game_status.control_values.clone(),
+    ));
+    if *last_values == values_key {
+        return;
+    }
+    *last_values = values_key;
+    game_status.control_displays =
+        build_control_displays(&game_status.current_level, &game_status.control_values);
+    game_status.require_refresh(Some(ui::REFRESH_GAME_FG));
+}
+
+pub fn refresh_aim_guide(
+    mut commands: Commands,
+    dyn_query: Query<Entity, With<GameDyn>>,
+    guide_query: Query<Entity, With<AimGuide>>,
+    game_status: Res<GameStatus>,
+    mut last_guide: Local<Option<(String, ControlValues, Option<usize>)>>,
+) {
+    let is_shown = game_status.mode == StatusMode::Deploying;
+    let guide_key = Some((
+        game_status.current_level.code.clone(),
+        game_status.control_values.clone(),
+        game_status.aimed_ball,
+    ));
+    if is_shown && *last_guide == guide_key && !guide_query.is_empty() {
+        return;
+    }
+    for entity in guide_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    if !is_shown {
+        *last_guide = None;
+        return;
+    }
+    *last_guide = guide_key;
+    let Ok(dyn_entity) = dyn_query.get_single() else {
+        return;
+    };
+    let simulation = Simulation::new(&game_status.current_level, &game_status.control_values);
+    // NOTE: The aimed stone gets the bright guide, the other launchable stones a faint one
+    let stones = launchable_stones(&game_status.current_level);
+    let aimed = game_status
+        .aimed_ball
+        .filter(|ball| stones.contains(ball))
+        .or(stones.first().copied());
+    let mut aimed_builder = PathBuilder::new();
+    let mut other_builder = PathBuilder::new();
+    for ball in stones {
+        if let Some(sim_ball) = simulation.ball(ball) {
+            let line_builder = if Some(ball) == aimed {
+                &mut aimed_builder
+            } else {
+                &mut other_builder
+            };
+            let pos = sim_ball.property.pos;
+            line_builder.move_to(pos);
+            line_builder.line_to(pos + sim_ball.property.v * AIM_GUIDE_RATIO);
+        }
+    }
+    commands.entity(dyn_entity).with_children(|parent| {
+        for (line_builder, alpha) in [(aimed_builder, 0.8), (other_builder, 0.3)] {
+            parent.spawn((
+                ShapeBundle {
+                    path: line_builder.build(),
+                    spatial: SpatialBundle {
+                        transform: Transform::from_xyz(0.0, 0.0, AIM_GUIDE_Z_LAYER),
+                        ..default()
+                    },
+                    ..default()
+                },
+                Stroke {
+                    color: theme::SECONDARY_COLOR.with_alpha(alpha),
+                    options: StrokeOptions::DEFAULT
+                        .with_line_width(AIM_GUIDE_W)
+                        .with_line_cap(LineCap::Round),
+                },
+                AimGuide,
+            ));
+        }
+    });
+}
+
+pub fn build_control_displays(
+    level: &LevelConfig,
+    values: &ControlValues,
+) -> Vec<BallControlDisplay> {
+    let mut displays = vec![];
+    for (ball, ball_config) in level.balls.iter().enumerate() {
+        let ball_type = Some(ball_config.ball_type.clone());
+        let x = values.get(level, ball, ControlField::X);
+        let y = values.get(level, ball, ControlField::Y);
+        match (x, y) {
+            (Some(x), Some(y)) => displays.push(BallControlDisplay {
+                ball_type: ball_type.clone(),
+                control_type: BallControlType::Move2D,
+                text: format!("{:.2}\n{:.2}", x, y),
+            }),
+            (Some(value), None) | (None, Some(value)) => displays.push(BallControlDisplay {
+                ball_type: ball_type.clone(),
+                control_type: BallControlType::Move1D,
+                text: format!("{:.2}", value),
+            }),
+            (None, None) => (),
+        }
+        if let Some(angle) = values.get(level, ball, ControlField::Angle) {
+            displays.push(BallControlDisplay {
+                ball_type: ball_type.clone(),
+                control_type: BallControlType::Angle,
+                text: format!("{:.2}", angle),
+            });
+        }
+        if let Some(force) = values.get(level, ball, ControlField::Force) {
+            displays.push(BallControlDisplay {
+                ball_type: ball_type.clone(),
+                control_type: BallControlType::Force,
+                text: format!("{:.2}", force),
+            });
+        }
+    }
+    displays
+}
+
+pub fn launchable_stones(level: &LevelConfig) -> Vec<usize> {
+    level
+        .balls
+        .iter()
+        .enumerate()
+        .filter(|(_, ball)| {
+            ball.ball_type == BallType::Stone
+                && ball.property.movement_type == BallMovementType::Movable
+                && (ball.control_params.force.is_some() || ball.control_params.angle.is_some())
+        })
+        .map(|(index, _)| index)
+        .collect()
+}
@@ -1,14 +1,15 @@
 use crate::app::theme;
 use crate::app::ui;
 use bevy::prelude::*;
-use bevy::utils::HashMap;
 use bevy_prototype_lyon::path::PathBuilder;
 use bevy_prototype_lyon::prelude::*;
 use bevy_tweening::lens::*;
 use bevy_tweening::Targetable;
 use circular_queue::CircularQueue;
+use serde::{Deserialize, Serialize};
 use std::fmt;
 
+pub mod attractor;
 pub mod bomb;
 pub mod goal;
 pub mod stone;
@@ -21,13 +22,13 @@ const BALL_LINE_W: f32 = ui::FONT_SIZE / 12.0;
 const BALL_OUTER_W: f32 = BALL_LINE_W * 3.0;
 const BALL_START_ANIME_L: u64 = 300;
 const BALL_END_ANIME_L: u64 = 300;
-const HIT_WINDOW: u8 = 5;
 
-#[derive(Component, Debug, PartialEq, Clone)]
+#[derive(Component, Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub enum BallType {
     Stone,
     Goal,
     Bomb,
+    Attractor,
 }
 
 impl BallType {
@@ -36,6 +37,14 @@ impl BallType {
             BallType::Stone => theme::SECONDARY_COLOR,
             BallType::Goal => theme::SUCCESS_COLOR,
             BallType::Bomb => theme::FAILURE_COLOR,
+            BallType::Attractor => theme::U_COLOR,
+        }
+    }
+    // NOTE: Attractors have no control icons of their own and share the neutral demo ones
+    pub fn control_icon_set(&self) -> String {
+        match self {
+            BallType::Attractor => String::from("demo"),
+            _ => self.to_string(),
         }
     }
 }
@@ -46,6 +55,7 @@ impl ToString for BallType {
             BallType::Stone => String::from("stone"),
             BallType::Goal => String::from("goal"),
             BallType::Bomb => String::from("bomb"),
+            BallType::Attractor => String::from("attractor"),
         }
     }
 }
@@ -79,7 +89,7 @@ impl ToString for BallControlType {
     }
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum BallMovementType {
     Movable,
     Fixed,
@@ -117,44 +127,98 @@ pub trait BallAbility {
                         ..default()
                     })
                     .with_children(|parent| {
-                        let mut last_pos = Vec2::ZERO;
-                        for tailing in ball.tailings().iter() {
-                            let mut line_builder = PathBuilder::new();
-                            line_builder.move_to(last_pos);
-                            line_builder.line_to(Vec2::new(
-                                tailing.x - ball.property.pos.x,
-                                tailing.y - ball.property.pos.y,
-                            ));
-                            parent.spawn((
-                                ShapeBundle {
-                                    path: line_builder.build(),
-                                    spatial: SpatialBundle {
-                                        transform: Transform::from_xyz(0.0, 0.0, z_layer + 0.0009),
-                                        ..default()
-                                    },
-                                    ..default()
-                                },
-                                Stroke {
-                                    color: ball.color().with_alpha(0.05),
-                                    options: StrokeOptions::DEFAULT
-                                        .with_line_width(ball.property.radius * 2.0)
-                                        .with_line_cap(LineCap::Round),
-                                },
-                            ));
-                            last_pos = *tailing - ball.property.pos;
-                        }
+                        build_tailings(
+                            parent,
+                            ball.tailings(),
+                            ball.property.pos,
+                            ball.property.radius,
+                            ball.color().with_alpha(0.05),
+                            z_layer + 0.0009,
+                        );
                     });
             });
         }
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BallProperty {
     pub radius: f32,
     pub pos: Vec2,
+    #[serde(default)]
     pub v: Vec2,
     pub movement_type: BallMovementType,
+    #[serde(default = "default_restitution")]
+    pub restitution: f32,
+    #[serde(default)]
+    pub friction: f32,
+    #[serde(default)]
+    pub damping: f32,
+    #[serde(default)]
+    pub mass: Option<f32>,
+    #[serde(default = "default_density")]
+    pub density: f32,
+}
+
+impl BallProperty {
+    pub fn new(pos: Vec2, radius: f32, movement_type: BallMovementType) -> Self {
+        Self {
+            radius,
+            pos,
+            v: Vec2::ZERO,
+            movement_type,
+            restitution: default_restitution(),
+            friction: 0.0,
+            damping: 0.0,
+            mass: None,
+            density: default_density(),
+        }
+    }
+    pub fn fixed_point(pos: Vec2) -> Self {
+        Self::new(pos, 0.0, BallMovementType::Fixed)
+    }
+    pub fn mass(&self) -> f32 {
+        self.mass.unwrap_or(self.density * self.radius.powi(2))
+    }
+}
+
+fn default_restitution() -> f32 {
+    1.0
+}
+
+fn default_density() -> f32 {
+    1.0
+}
+
+// NOTE: Attractors pull (positive strength) or push (negative strength) movable balls in range
+// with an acceleration of strength / distance ^ falloff.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WellParams {
+    pub strength: f32,
+    pub range: f32,
+    pub falloff: f32,
+}
+
+impl Default for WellParams {
+    fn default() -> Self {
+        Self {
+            strength: 500.0,
+            range: 240.0,
+            falloff: 2.0,
+        }
+    }
+}
+
+impl WellParams {
+    pub fn acceleration(&self, center: Vec2, radius: f32, pos: Vec2) -> Vec2 {
+        let offset = center - pos;
+        let distance = offset.length();
+        if distance == 0.0 || distance > self.range {
+            return Vec2::ZERO;
+        }
+        offset / distance * self.strength / distance.max(radius).powf(self.falloff)
+    }
 }
 
 pub struct BallAnimeParams {
@@ -162,7 +226,8 @@ pub struct BallAnimeParams {
     pub alpha: f32,
 }
 
-#[derive(Debug, Default, Clone)]
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct BallControlParams {
     pub x: Option<(f32, f32)>,
     pub y: Option<(f32, f32)>,
@@ -179,11 +244,11 @@ pub struct BallControlDisplay {
 
 #[derive(Component)]
 pub struct Ball {
+    pub index: usize,
     pub state: BallState,
     pub property: BallProperty,
     pub anime_params: BallAnimeParams,
     pub control_params: BallControlParams,
-    hit_entity_counter: HashMap<Entity, u8>,
     ability: Box<dyn BallAbility + Send + Sync>,
     tailings: CircularQueue<Vec2>,
     tailing_counter: u8,
@@ -194,6 +259,7 @@ pub struct Ball {
 
 impl Ball {
     pub fn create_sprite(
+        index: usize,
         ball_type: BallType,
         parent: &mut ChildBuilder,
         bundle: impl Bundle,
@@ -241,6 +307,7 @@ impl Ball {
                 .id();
         });
         let ball = Ball::new(
+            index,
             ball_type,
             property,
             control_params,
@@ -251,6 +318,7 @@ impl Ball {
         root_entity_command.insert(ball);
     }
     pub fn new(
+        index: usize,
         ball_type: BallType,
         property: BallProperty,
         control_params: BallControlParams,
@@ -259,10 +327,12 @@ impl Ball {
         dyn_entity: Entity,
     ) -> Self {
         Self {
+            index,
             ability: match ball_type {
                 BallType::Stone => Box::new(stone::Ability),
                 BallType::Goal => Box::new(goal::Ability),
                 BallType::Bomb => Box::new(bomb::Ability),
+                BallType::Attractor => Box::new(attractor::Ability),
             },
             property,
             anime_params: BallAnimeParams {
@@ -270,9 +340,8 @@ impl Ball {
                 alpha: 0.0,
             },
             control_params,
-            hit_entity_counter: HashMap::new(),
             state: BallState::Created,
-            tailings: CircularQueue::with_capacity(TAILING_SIZE),
+            tailings: new_tailings(),
             tailing_counter: 0,
             root_entity,
             bg_entity,
@@ -289,27 +358,6 @@ impl Ball {
         self.property.pos = pos;
         self.record_tailing(self.property.pos);
     }
-    pub fn store_hit_entity(&mut self, e: Entity) {
-        self.hit_entity_counter.insert(e, HIT_WINDOW);
-    }
-    pub fn check_hit_window(&mut self, e: Entity) -> bool {
-        if let Some(count) = self.hit_entity_counter.get_mut(&e) {
-            let count_value = *count;
-            dbg!(count_value);
-            if count_value > 0 {
-                self.hit_entity_counter.insert(e, count_value - 1);
-                return false;
-            } else {
-                self.hit_entity_counter.remove(&e);
-                return true;
-            }
-        } else {
-            return true;
-        }
-    }
-    pub fn clear_hit_counter(&mut self) {
-        self.hit_entity_counter.clear();
-    }
     pub fn update_v(&mut self, v: Vec2) {
         self.property.v = v;
     }
@@ -322,12 +370,6 @@ impl Ball {
     pub fn dyn_entity(&self) -> Entity {
         self.dyn_entity
     }
-    pub fn travel(&mut self) -> Vec2 {
-        if self.property.movement_type == BallMovementType::Movable {
-            self.update_pos(self.property.pos + self.property.v);
-        }
-        self.property.pos
-    }
     pub fn jump(&mut self, pos: Vec2) {
         self.property.pos = pos;
         self.record_tailing(self.property.pos);
@@ -429,6 +471,44 @@ impl Lens<Ball> for BallAnimeLens {
     }
 }
 
+pub fn new_tailings() -> CircularQueue<Vec2> {
+    CircularQueue::with_capacity(TAILING_SIZE)
+}
+
+// NOTE: Tailings are drawn relative to the current position of the ball
+pub fn build_tailings(
+    parent: &mut ChildBuilder,
+    tailings: &CircularQueue<Vec2>,
+    pos: Vec2,
+    radius: f32,
+    color: Color,
+    z_layer: f32,
+) {
+    let mut last_pos = Vec2::ZERO;
+    for tailing in tailings.iter() {
+        let mut line_builder = PathBuilder::new();
+        line_builder.move_to(last_pos);
+        line_builder.line_to(*tailing - pos);
+        parent.spawn((
+            ShapeBundle {
+                path: line_builder.build(),
+                spatial: SpatialBundle {
+                    transform: Transform::from_xyz(0.0, 0.0, z_layer),
+                    ..default()
+                },
+                ..default()
+            },
+            Stroke {
+                color,
+                options: StrokeOptions::DEFAULT
+                    .with_line_width(radius * 2.0)
+                    .with_line_cap(LineCap::Round),
+            },
+        ));
+        last_pos = *tailing - pos;
+    }
+}
+
 const FIXED_BG_LINE_W: f32 = ui::FONT_SIZE / 36.0;
 const FIXED_BG_LINE_P: f32 = FIXED_BG_LINE_W * 6.0;
 
@@ -0,0 +1,188 @@
+use super::*;
+use crate::app::theme;
+use bevy_tweening::*;
+use std::time::Duration;
+
+const RING_COUNT: u8 = 3;
+const RING_GAP: f32 = BALL_OUTER_W * 2.0;
+
+pub struct Ability;
+
+impl BallAbility for Ability {
+    fn ball_type(&self) -> BallType {
+        BallType::Attractor
+    }
+    fn setup_starting_anime(&self, commands: &mut Commands, ball: &Ball) {
+        setup_starting_anime(commands, ball);
+    }
+    fn setup_ending_anime(&self, commands: &mut Commands, ball: &Ball) {
+        setup_ending_anime(commands, ball);
+    }
+    fn update_starting_anime(&self, commands: &mut Commands, ball: &Ball) {
+        update_starting_anime(commands, ball);
+    }
+    fn update_ending_anime(&self, commands: &mut Commands, ball: &Ball) {
+        update_ending_anime(commands, ball);
+    }
+}
+
+fn setup_starting_anime(commands: &mut Commands, ball: &Ball) {
+    if let Some(mut entity_commands) = commands.get_entity(ball.root_entity()) {
+        let tween = Tween::new(
+            EaseFunction::QuadraticOut,
+            Duration::from_millis(BALL_START_ANIME_L),
+            BallAnimeLens {
+                start_radius: 0.0,
+                start_color_alpha: 0.0,
+                end_radius: ball.property.radius,
+                end_color_alpha: 1.0,
+            },
+        )
+        .with_completed_event(STARTING_DONE_EVENT);
+        entity_commands.insert(Animator::new(tween));
+    }
+}
+
+fn setup_ending_anime(commands: &mut Commands, ball: &Ball) {
+    if let Some(mut entity_commands) = commands.get_entity(ball.root_entity()) {
+        let tween = Tween::new(
+            EaseFunction::QuadraticIn,
+            Duration::from_millis(BALL_END_ANIME_L),
+            BallAnimeLens {
+                start_radius: ball.property.radius,
+                start_color_alpha: 0.3,
+                end_radius: 0.0,
+                end_color_alpha: 0.0,
+            },
+        )
+        .with_completed_event(ENDING_DONE_EVENT);
+        entity_commands.insert(Animator::new(tween));
+    }
+}
+
+fn update_starting_anime(commands: &mut Commands, ball: &Ball) {
+    if ball.state != BallState::Starting {
+        return;
+    }
+    if let Some(mut entity_commands) = commands.get_entity(ball.bg_entity()) {
+        entity_commands.despawn_descendants();
+        entity_commands.with_children(|parent| {
+            let z_layer = if ball.property.movement_type == BallMovementType::FixedReversed {
+                0.0
+            } else {
+                1.0
+            };
+            parent
+                .spawn(SpriteBundle {
+                    transform: Transform::from_xyz(0.0, 0.0, z_layer),
+                    ..default()
+                })
+                .with_children(|parent| {
+                    for i in 1..=RING_COUNT {
+                        let shape = shapes::Circle {
+                            radius: ball.anime_params.radius + RING_GAP * i as f32,
+                            center: Vec2::new(0.0, 0.0),
+                        };
+                        let ring_alpha = ball.anime_params.alpha / (i + 1) as f32;
+                        parent.spawn((
+                            ShapeBundle {
+                                path: GeometryBuilder::build_as(&shape),
+                                spatial: SpatialBundle {
+                                    transform: Transform::from_xyz(0.0, 0.0, z_layer + 0.001),
+                                    ..default()
+                                },
+                                ..default()
+                            },
+                            Stroke::new(ball.color().with_alpha(ring_alpha), BALL_LINE_W),
+                        ));
+                    }
+                    let shape = shapes::Circle {
+                        radius: ball.anime_params.radius - BALL_OUTER_W,
+                        center: Vec2::new(0.0, 0.0),
+                    };
+                    let fill_color = if ball.property.movement_type == BallMovementType::Movable {
+                        ball.color().with_alpha(ball.anime_params.alpha)
+                    } else {
+                        theme::BG_COLOR
+                    };
+                    parent.spawn((
+                        ShapeBundle {
+                            path: GeometryBuilder::build_as(&shape),
+                            spatial: SpatialBundle {
+                                transform: Transform::from_xyz(0.0, 0.0, z_layer + 0.003),
+                                ..default()
+                            },
+                            ..default()
+                        },
+                        Stroke::new(
+                            ball.color().with_alpha(ball.anime_params.alpha),
+                            BALL_LINE_W,
+                        ),
+                        Fill::color(fill_color),
+                    ));
+                    let core_shape = shapes::Circle {
+                        radius: (ball.anime_params.radius - BALL_OUTER_W) * 0.3,
+                        center: Vec2::new(0.0, 0.0),
+                    };
+                    parent.spawn((
+                        ShapeBundle {
+                            path: GeometryBuilder::build_as(&core_shape),
+                            spatial: SpatialBundle {
+                                transform: Transform::from_xyz(0.0, 0.0, z_layer + 0.004),
+                                ..default()
+                            },
+                            ..default()
+                        },
+                        Fill::color(ball.color().with_alpha(ball.anime_params.alpha)),
+                    ));
+                    let shape = shapes::Circle {
+                        radius: ball.anime_params.radius,
+                        center: Vec2::new(0.0, 0.0),
+                    };
+                    parent.spawn((
+                        ShapeBundle {
+                            path: GeometryBuilder::build_as(&shape),
+                            spatial: SpatialBundle {
+                                transform: Transform::from_xyz(0.0, 0.0, z_layer + 0.005),
+                                ..default()
+                            },
+                            ..default()
+                        },
+                        Stroke::new(
+                            ball.color().with_alpha(ball.anime_params.alpha),
+                            BALL_LINE_W,
+                        ),
+                    ));
+                });
+        });
+    }
+}
+
+fn update_ending_anime(commands: &mut Commands, ball: &Ball) {
+    if ball.state != BallState::Ending {
+        return;
+    }
+    if let Some(mut entity_commands) = commands.get_entity(ball.bg_entity()) {
+        entity_commands.despawn_descendants();
+        entity_commands.with_children(|parent| {
+            parent
+                .spawn(SpriteBundle {
+                    transform: Transform::from_xyz(0.0, 0.0, 0.1),
+                    ..default()
+                })
+                .with_children(|parent| {
+                    let shape = shapes::Circle {
+                        radius: ball.anime_params.radius,
+                        center: Vec2::new(0.0, 0.0),
+                    };
+                    parent.spawn((
+                        ShapeBundle {
+                            path: GeometryBuilder::build_as(&shape),
+                            ..default()
+                        },
+                        Fill::color(ball.color().with_alpha(ball.anime_params.alpha)),
+                    ));
+                });
+        });
+    }
+}
@@ -1,71 +1,193 @@
-use super::ball::*;
+use super::{ball::*, simulation::SimBall};
 use bevy::prelude::*;
-use std::{cell::RefCell, collections::HashMap, rc::Rc};
 
-#[derive(Clone)]
-pub struct HitMoveInfo {
-    pub hit_type: HitType,
-    pub opponent_entity: Entity,
-    pub opponent_property: BallProperty,
-}
-
-#[derive(Clone)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum HitAction {
-    Move(HitMoveInfo),
+    Move,
     Success,
     Failure,
 }
 
-#[derive(Clone, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum HitType {
     None,
     Outside,
     Inside,
 }
 
-type EntityBall<'a> = (Rc<RefCell<Entity>>, Rc<RefCell<&'a Ball>>);
+#[derive(Clone, Debug)]
+pub struct HitInfo {
+    pub hit_type: HitType,
+    pub action: HitAction,
+    pub time: f32,
+    pub b1: usize,
+    pub b2: usize,
+}
+
+#[derive(Clone, Debug)]
+struct SweptBounds {
+    index: usize,
+    min: Vec2,
+    max: Vec2,
+}
+
+// NOTE: Find the earliest contact of running balls within the given duration (in ticks). Ties are
+// resolved by ball order to keep the simulation deterministic.
+pub fn find_first_hit(balls: &[SimBall], duration: f32) -> Option<HitInfo> {
+    let mut first_hit: Option<HitInfo> = None;
+    for (i, j) in candidate_pairs(balls, duration) {
+        let b1 = &balls[i];
+        let b2 = &balls[j];
+        if let Some((hit_type, time)) = detect_hit_time(&b1.property, &b2.property, duration) {
+            if first_hit.as_ref().map_or(true, |hit| time < hit.time) {
+                first_hit = Some(HitInfo {
+                    hit_type,
+                    action: hit_action(&b1.ball_type, &b2.ball_type),
+                    time,
+                    b1: i,
+                    b2: j,
+                });
+            }
+        }
+    }
+    first_hit
+}
 
-pub fn build_hit_map(
-    ball_query: &Query<(Entity, &mut Ball, &mut Transform), With<Ball>>,
-) -> HashMap<Entity, Vec<HitAction>> {
-    let mut balls: Vec<EntityBall> = Vec::new();
-    for (e, b, _) in ball_query.iter() {
-        balls.push((Rc::new(RefCell::new(e)), Rc::new(RefCell::new(b))));
+// NOTE: Broad phase with sweep and prune on the swept bounds along x. FixedReversed balls are
+// hit from the inside, so they are paired with every other running ball instead.
+pub fn candidate_pairs(balls: &[SimBall], duration: f32) -> Vec<(usize, usize)> {
+    let mut containers: Vec<usize> = Vec::new();
+    let mut bounds: Vec<SweptBounds> = Vec::new();
+    for (index, ball) in balls.iter().enumerate() {
+        if !ball.is_running {
+            continue;
+        }
+        if ball.property.movement_type == BallMovementType::FixedReversed {
+            containers.push(index);
+        } else {
+            bounds.push(swept_bounds(index, &ball.property, duration));
+        }
+    }
+    bounds.sort_by(|a, b| a.min.x.total_cmp(&b.min.x).then(a.index.cmp(&b.index)));
+    let mut pairs: Vec<(usize, usize)> = Vec::new();
+    for (k, b1) in bounds.iter().enumerate() {
+        for b2 in bounds[k + 1..].iter() {
+            if b2.min.x > b1.max.x {
+                break;
+            }
+            if b1.min.y <= b2.max.y && b2.min.y <= b1.max.y {
+                pairs.push((b1.index.min(b2.index), b1.index.max(b2.index)));
+            }
+        }
+    }
+    for container in containers.iter() {
+        for b in bounds.iter() {
+            pairs.push((b.index.min(*container), b.index.max(*container)));
+        }
     }
-    let mut hit_map: HashMap<Entity, Vec<HitAction>> = HashMap::new();
-    for (i, (e1, b1)) in balls.iter().enumerate() {
-        if b1.borrow().state != BallState::Running {
+    pairs.sort_unstable();
+    pairs
+}
+
+#[derive(Clone, Debug)]
+pub struct WallHitInfo {
+    pub ball: usize,
+    pub wall: usize,
+    pub time: f32,
+    pub contact: Vec2,
+}
+
+pub fn find_first_wall_hit(
+    balls: &[SimBall],
+    walls: &[(Vec2, Vec2)],
+    duration: f32,
+) -> Option<WallHitInfo> {
+    let mut first_hit: Option<WallHitInfo> = None;
+    for (ball, b) in balls.iter().enumerate() {
+        if !b.is_running || b.property.movement_type != BallMovementType::Movable {
             continue;
         }
-        for (j, (e2, b2)) in balls.iter().enumerate() {
-            if b2.borrow().state != BallState::Running {
+        let bounds = swept_bounds(ball, &b.property, duration);
+        for (wall, (start, end)) in walls.iter().enumerate() {
+            if start.max(*end).cmplt(bounds.min).any() || start.min(*end).cmpgt(bounds.max).any() {
                 continue;
             }
-            if j > i {
-                let hit_type = detect_hit(&b1.borrow(), &b2.borrow());
-                if hit_type != HitType::None {
-                    record_hit_action(
-                        &mut hit_map,
-                        hit_type,
-                        *e1.borrow(),
-                        &b1.borrow(),
-                        *e2.borrow(),
-                        &b2.borrow(),
-                    )
+            if let Some((time, contact)) = detect_wall_hit_time(&b.property, *start, *end, duration)
+            {
+                if first_hit.as_ref().map_or(true, |hit| time < hit.time) {
+                    first_hit = Some(WallHitInfo {
+                        ball,
+                        wall,
+                        time,
+                        contact,
+                    });
                 }
             }
         }
     }
-    hit_map
+    first_hit
+}
+
+// NOTE: Swept circle against a segment, the segment is treated as a line part with two end points.
+// Returns the time of impact and the contact point on the segment.
+pub fn detect_wall_hit_time(
+    bp: &BallProperty,
+    start: Vec2,
+    end: Vec2,
+    duration: f32,
+) -> Option<(f32, Vec2)> {
+    let v = travel_v(bp);
+    if v == Vec2::ZERO {
+        return None;
+    }
+    let mut first_hit: Option<(f32, Vec2)> = None;
+    let direction = end - start;
+    let mut normal = direction.perp().normalize();
+    let mut distance = (bp.pos - start).dot(normal);
+    if distance < 0.0 {
+        normal = -normal;
+        distance = -distance;
+    }
+    let approach = -v.dot(normal);
+    if approach > 0.0 {
+        let time = ((distance - bp.radius) / approach).max(0.0);
+        if time <= duration {
+            let center = bp.pos + v * time;
+            let ratio = (center - start).dot(direction) / direction.length_squared();
+            if (0.0..=1.0).contains(&ratio) {
+                first_hit = Some((time, start + direction * ratio));
+            }
+        }
+    }
+    for point in [start, end] {
+        let point_bp = BallProperty::fixed_point(point);
+        if let Some((_, time)) = detect_hit_time(bp, &point_bp, duration) {
+            if first_hit.map_or(true, |(first_time, _)| time < first_time) {
+                first_hit = Some((time, point));
+            }
+        }
+    }
+    first_hit
 }
 
-pub fn detect_hit(b1: &Ball, b2: &Ball) -> HitType {
-    let pos1 = b1.property.pos;
-    let pos2 = b2.property.pos;
-    let r1 = b1.property.radius;
-    let r2 = b2.property.radius;
-    if b1.property.movement_type != BallMovementType::FixedReversed
-        && b2.property.movement_type != BallMovementType::FixedReversed
+fn swept_bounds(index: usize, bp: &BallProperty, duration: f32) -> SweptBounds {
+    let start = bp.pos;
+    let end = bp.pos + travel_v(bp) * duration;
+    let radius = Vec2::splat(bp.radius);
+    SweptBounds {
+        index,
+        min: start.min(end) - radius,
+        max: start.max(end) + radius,
+    }
+}
+
+pub fn detect_hit(bp1: &BallProperty, bp2: &BallProperty) -> HitType {
+    let pos1 = bp1.pos;
+    let pos2 = bp2.pos;
+    let r1 = bp1.radius;
+    let r2 = bp2.radius;
+    if bp1.movement_type != BallMovementType::FixedReversed
+        && bp2.movement_type != BallMovementType::FixedReversed
     {
         if pos1.distance(pos2) > r1 + r2 {
             return HitType::None;
@@ -88,108 +210,122 @@ pub fn detect_hit(b1: &Ball, b2: &Ball) -> HitType {
     }
 }
 
-fn record_hit_action(
-    hit_map: &mut HashMap<Entity, Vec<HitAction>>,
-    hit_type: HitType,
-    e1: Entity,
-    b1: &Ball,
-    e2: Entity,
-    b2: &Ball,
-) {
-    let hit_status: &str;
-    let e1_action: HitAction;
-    let e2_action: HitAction;
-    match b1.ball_type() {
-        BallType::Stone => match b2.ball_type() {
-            BallType::Stone => {
-                hit_status = "hit";
-            }
-            BallType::Goal => {
-                hit_status = "success";
-            }
-            BallType::Bomb => {
-                hit_status = "failure";
-            }
+// NOTE: Swept circle test, returns the time of impact in [0, duration] if the balls touch while
+// moving toward each other (Outside) or while the inner ball moves out of the container (Inside).
+pub fn detect_hit_time(
+    bp1: &BallProperty,
+    bp2: &BallProperty,
+    duration: f32,
+) -> Option<(HitType, f32)> {
+    let is_reversed1 = bp1.movement_type == BallMovementType::FixedReversed;
+    let is_reversed2 = bp2.movement_type == BallMovementType::FixedReversed;
+    if is_reversed1 && is_reversed2 {
+        return None;
+    }
+    let d = bp1.pos - bp2.pos;
+    let w = travel_v(bp1) - travel_v(bp2);
+    let a = w.dot(w);
+    if a == 0.0 {
+        return None;
+    }
+    let b = d.dot(w);
+    if !is_reversed1 && !is_reversed2 {
+        let contact = bp1.radius + bp2.radius;
+        let c = d.dot(d) - contact * contact;
+        if b >= 0.0 {
+            return None;
+        }
+        if c <= 0.0 {
+            return Some((HitType::Outside, 0.0));
+        }
+        let discriminant = b * b - a * c;
+        if discriminant < 0.0 {
+            return None;
+        }
+        let time = (-b - discriminant.sqrt()) / a;
+        if time <= duration {
+            return Some((HitType::Outside, time.max(0.0)));
+        }
+        return None;
+    }
+    let contact = (bp1.radius - bp2.radius).abs();
+    let c = d.dot(d) - contact * contact;
+    if c >= 0.0 {
+        // NOTE: A ball on the rim is pushed back in, a ball completely outside does not touch it
+        let outer = bp1.radius + bp2.radius;
+        if b > 0.0 && d.dot(d) < outer * outer {
+            return Some((HitType::Inside, 0.0));
+        }
+        return None;
+    }
+    let discriminant = b * b - a * c;
+    let time = (-b + discriminant.sqrt()) / a;
+    if time <= duration {
+        Some((HitType::Inside, time.max(0.0)))
+    } else {
+        None
+    }
+}
+
+pub fn hit_action(t1: &BallType, t2: &BallType) -> HitAction {
+    match t1 {
+        BallType::Stone => match t2 {
+            BallType::Stone => HitAction::Move,
+            BallType::Goal => HitAction::Success,
+            BallType::Bomb => HitAction::Failure,
+            // NOTE: The stone is swallowed if it falls into an attractor
+            BallType::Attractor => HitAction::Failure,
         },
-        BallType::Goal => match b2.ball_type() {
-            BallType::Stone => {
-                hit_status = "success";
-            }
-            BallType::Goal => {
-                hit_status = "hit";
-            }
-            BallType::Bomb => {
-                hit_status = "hit";
-            }
+        BallType::Goal => match t2 {
+            BallType::Stone => HitAction::Success,
+            _ => HitAction::Move,
         },
-        BallType::Bomb => match b2.ball_type() {
-            BallType::Stone => {
-                hit_status = "failure";
-            }
-            BallType::Goal => {
-                hit_status = "hit";
-            }
-            BallType::Bomb => {
-                hit_status = "hit";
-            }
+        BallType::Bomb => match t2 {
+            BallType::Stone => HitAction::Failure,
+            _ => HitAction::Move,
+        },
+        BallType::Attractor => match t2 {
+            BallType::Stone => HitAction::Failure,
+            _ => HitAction::Move,
         },
     }
-    match hit_status {
-        "hit" => {
-            e1_action = HitAction::Move(HitMoveInfo {
-                hit_type: hit_type.clone(),
-                opponent_entity: e2,
-                opponent_property: b2.property.clone(),
-            });
-            e2_action = HitAction::Move(HitMoveInfo {
-                hit_type: hit_type.clone(),
-                opponent_entity: e1,
-                opponent_property: b1.property.clone(),
-            });
-        }
-        "success" => {
-            e1_action = HitAction::Success;
-            e2_action = HitAction::Success;
-        }
-        "failure" => {
-            e1_action = HitAction::Failure;
-            e2_action = HitAction::Failure;
-        }
-        _ => return,
-    }
-    if let Some(actions) = hit_map.get_mut(&e1) {
-        actions.push(e1_action);
-    } else {
-        hit_map.insert(e1, vec![e1_action]);
-    }
-    if let Some(actions) = hit_map.get_mut(&e2) {
-        actions.push(e2_action);
+}
+
+fn travel_v(bp: &BallProperty) -> Vec2 {
+    if bp.movement_type == BallMovementType::Movable {
+        bp.v
     } else {
-        hit_map.insert(e2, vec![e2_action]);
+        Vec2::ZERO
     }
 }
 
 pub fn calcuate_v_after_hit(hit_type: &HitType, bp1: &BallProperty, bp2: &BallProperty) -> Vec2 {
     let v1 = bp1.v;
     let v2 = bp2.v;
-    let m1 = bp1.radius.powi(2);
-    let m2 = bp2.radius.powi(2);
+    let m1 = bp1.mass();
+    let m2 = bp2.mass();
     let vp = if *hit_type == HitType::Outside {
         bp1.pos - bp2.pos
     } else {
         bp2.pos - bp1.pos
     };
-    if bp2.movement_type == BallMovementType::Movable {
-        v1 - 2.0 * m2 / (m1 + m2) * (v1 - v2).dot(vp) / vp.length().powi(2) * vp
+    let normal = vp.normalize_or_zero();
+    let restitution = bp1.restitution * bp2.restitution;
+    let friction = ((bp1.friction + bp2.friction) * 0.5).clamp(0.0, 1.0);
+    let new_v = if bp2.movement_type == BallMovementType::Movable {
+        v1 - (1.0 + restitution) * m2 / (m1 + m2) * (v1 - v2).dot(normal) * normal
     } else {
         if bp2.movement_type == BallMovementType::FixedReversed && v1.dot(vp) > 0.0 {
             // NOTE: If v1 is toward center, keep v1 not change to make sure ball will not move
             // outside
-            v1
+            return v1;
         } else {
-            v1 - 2.0 * v1.dot(vp) / vp.length().powi(2) * vp
+            v1 - (1.0 + restitution) * v1.dot(normal) * normal
         }
-    }
+    };
+    // NOTE: Friction only slows down the tangential part of the velocity
+    let normal_v = new_v.dot(normal) * normal;
+    normal_v + (new_v - normal_v) * (1.0 - friction)
 }
 
 // pub fn calcuate_v_after_hit(hit_type: &HitType, bp1: &BallProperty, bp2: &BallProperty) -> Vec2 {
@@ -212,3 +348,169 @@ pub fn calcuate_v_after_hit(hit_type: &HitType, bp1: &BallProperty, bp2: &BallPr
 //         dv * ((m1 - m2) * v1.length() + 2.0 * m2 * v2.length()) / (m1 + m2)
 //     }
 // }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    fn movable(pos: Vec2, v: Vec2, radius: f32) -> BallProperty {
+        let mut bp = BallProperty::new(pos, radius, BallMovementType::Movable);
+        bp.v = v;
+        bp
+    }
+
+    fn sim_ball(ball_type: BallType, property: BallProperty) -> SimBall {
+        SimBall {
+            ball_type,
+            property,
+            well: None,
+            is_running: true,
+        }
+    }
+
+    #[test]
+    fn hit_time_of_balls_moving_toward_each_other() {
+        let bp1 = movable(Vec2::new(0.0, 0.0), Vec2::new(10.0, 0.0), 5.0);
+        let bp2 = BallProperty::new(Vec2::new(30.0, 0.0), 5.0, BallMovementType::Fixed);
+        let (hit_type, time) = detect_hit_time(&bp1, &bp2, 3.0).unwrap();
+        assert_eq!(hit_type, HitType::Outside);
+        assert!((time - 2.0).abs() < 1e-5);
+        // NOTE: Too far to reach within the duration
+        assert!(detect_hit_time(&bp1, &bp2, 1.5).is_none());
+    }
+
+    #[test]
+    fn no_hit_when_moving_apart() {
+        let bp1 = movable(Vec2::new(0.0, 0.0), Vec2::new(-10.0, 0.0), 5.0);
+        let bp2 = BallProperty::new(Vec2::new(12.0, 0.0), 5.0, BallMovementType::Fixed);
+        assert!(detect_hit_time(&bp1, &bp2, 10.0).is_none());
+    }
+
+    #[test]
+    fn fast_ball_does_not_tunnel_through() {
+        // NOTE: The ball passes the whole obstacle within a single tick
+        let bp1 = movable(Vec2::new(0.0, 0.0), Vec2::new(100.0, 0.0), 2.0);
+        let bp2 = BallProperty::new(Vec2::new(50.0, 0.0), 2.0, BallMovementType::Fixed);
+        let (_, time) = detect_hit_time(&bp1, &bp2, 1.0).unwrap();
+        assert!((time - 0.46).abs() < 1e-5);
+    }
+
+    #[test]
+    fn hit_time_inside_a_container() {
+        let bp1 = movable(Vec2::new(0.0, 0.0), Vec2::new(10.0, 0.0), 10.0);
+        let bp2 = BallProperty::new(Vec2::ZERO, 100.0, BallMovementType::FixedReversed);
+        let (hit_type, time) = detect_hit_time(&bp1, &bp2, 10.0).unwrap();
+        assert_eq!(hit_type, HitType::Inside);
+        assert!((time - 9.0).abs() < 1e-5);
+    }
+
+    // NOTE: A ball moving away outside a container used to get an immediate inside hit, which
+    // pulled it through the rim
+    #[test]
+    fn no_hit_for_a_ball_outside_a_container() {
+        let bp2 = BallProperty::new(Vec2::ZERO, 50.0, BallMovementType::FixedReversed);
+        let outside = movable(Vec2::new(200.0, 0.0), Vec2::new(5.0, 0.0), 10.0);
+        assert_eq!(detect_hit_time(&outside, &bp2, 1.0), None);
+        let on_rim = movable(Vec2::new(45.0, 0.0), Vec2::new(5.0, 0.0), 10.0);
+        assert_eq!(
+            detect_hit_time(&on_rim, &bp2, 1.0),
+            Some((HitType::Inside, 0.0))
+        );
+    }
+
+    #[test]
+    fn first_hit_is_the_earliest_one() {
+        let balls = vec![
+            sim_ball(
+                BallType::Stone,
+                movable(Vec2::ZERO, Vec2::new(10.0, 0.0), 5.0),
+            ),
+            sim_ball(
+                BallType::Bomb,
+                BallProperty::new(Vec2::new(60.0, 0.0), 5.0, BallMovementType::Fixed),
+            ),
+            sim_ball(
+                BallType::Goal,
+                BallProperty::new(Vec2::new(30.0, 0.0), 5.0, BallMovementType::Fixed),
+            ),
+        ];
+        let hit = find_first_hit(&balls, 10.0).unwrap();
+        assert_eq!((hit.b1, hit.b2), (0, 2));
+        assert_eq!(hit.action, HitAction::Success);
+        assert!((hit.time - 2.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn candidate_pairs_cover_every_hit() {
+        let mut rng = StdRng::seed_from_u64(5);
+        for _ in 0..50 {
+            let mut balls: Vec<SimBall> = (0..12)
+                .map(|_| {
+                    let pos = Vec2::new(rng.gen_range(-200.0..200.0), rng.gen_range(-200.0..200.0));
+                    let radius = rng.gen_range(2.0..30.0);
+                    if rng.gen_bool(0.5) {
+                        let v = Vec2::new(rng.gen_range(-40.0..40.0), rng.gen_range(-40.0..40.0));
+                        sim_ball(BallType::Stone, movable(pos, v, radius))
+                    } else {
+                        sim_ball(
+                            BallType::Stone,
+                            BallProperty::new(pos, radius, BallMovementType::Fixed),
+                        )
+                    }
+                })
+                .collect();
+            balls.push(sim_ball(
+                BallType::Stone,
+                BallProperty::new(Vec2::ZERO, 320.0, BallMovementType::FixedReversed),
+            ));
+            balls[3].is_running = false;
+            let pairs = candidate_pairs(&balls, 1.0);
+            for i in 0..balls.len() {
+                for j in i + 1..balls.len() {
+                    if !balls[i].is_running || !balls[j].is_running {
+                        assert!(!pairs.contains(&(i, j)));
+                        continue;
+                    }
+                    if detect_hit_time(&balls[i].property, &balls[j].property, 1.0).is_some() {
+                        assert!(pairs.contains(&(i, j)), "missing pair ({}, {})", i, j);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn wall_hit_on_the_segment_and_its_ends() {
+        let start = Vec2::new(50.0, -20.0);
+        let end = Vec2::new(50.0, 20.0);
+        let bp = movable(Vec2::ZERO, Vec2::new(10.0, 0.0), 5.0);
+        let (time, contact) = detect_wall_hit_time(&bp, start, end, 10.0).unwrap();
+        assert!((time - 4.5).abs() < 1e-5);
+        assert_eq!(contact, Vec2::new(50.0, 0.0));
+        // NOTE: Passing beside the segment only touches its end point
+        let bp = movable(Vec2::new(0.0, 23.0), Vec2::new(10.0, 0.0), 5.0);
+        let (_, contact) = detect_wall_hit_time(&bp, start, end, 10.0).unwrap();
+        assert_eq!(contact, end);
+        let bp = movable(Vec2::new(0.0, 30.0), Vec2::new(10.0, 0.0), 5.0);
+        assert!(detect_wall_hit_time(&bp, start, end, 10.0).is_none());
+        // NOTE: Moving away from the wall never hits it
+        let bp = movable(Vec2::ZERO, Vec2::new(-10.0, 0.0), 5.0);
+        assert!(detect_wall_hit_time(&bp, start, end, 10.0).is_none());
+    }
+
+    #[test]
+    fn first_wall_hit_is_the_earliest_one() {
+        let balls = vec![sim_ball(
+            BallType::Stone,
+            movable(Vec2::ZERO, Vec2::new(10.0, 10.0), 5.0),
+        )];
+        let walls = [
+            (Vec2::new(80.0, -100.0), Vec2::new(80.0, 100.0)),
+            (Vec2::new(-100.0, 40.0), Vec2::new(100.0, 40.0)),
+        ];
+        let hit = find_first_wall_hit(&balls, &walls, 10.0).unwrap();
+        assert_eq!(hit.wall, 1);
+        assert!((hit.time - 3.5).abs() < 1e-5);
+    }
+}
@@ -0,0 +1,306 @@
+use super::{
+    simulation::{ControlValues, HitEvent, Simulation},
+    *,
+};
+use crate::app::{theme, ui};
+use bevy::utils::HashMap;
+use bevy_prototype_lyon::prelude::*;
+use std::collections::VecDeque;
+
+const DEBUG_TOGGLE_KEY: KeyCode = KeyCode::F3;
+const MAX_LOG_LINES: usize = 12;
+const VECTOR_SCALE: f32 = 10.0;
+const DEBUG_LINE_W: f32 = ui::SPACE_SIZE * 0.3;
+const DEBUG_FS: f32 = ui::FONT_SIZE * 0.4;
+const DEBUG_GAP: f32 = ui::FONT_SIZE * 0.3;
+
+// NOTE: The hits are counted per ball and opponent over the whole attempt, walls are kept apart
+// from the balls
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum HitOpponent {
+    Ball(usize),
+    Wall(usize),
+}
+
+#[derive(Resource, Debug, Default)]
+pub struct DebugOverlay {
+    pub is_enabled: bool,
+    log: VecDeque<String>,
+    hit_counts: HashMap<(usize, HitOpponent), u32>,
+    last_tick: u32,
+    version: u32,
+}
+
+impl DebugOverlay {
+    pub fn toggle(&mut self) {
+        self.is_enabled = !self.is_enabled;
+    }
+    pub fn clear(&mut self) {
+        self.log.clear();
+        self.hit_counts.clear();
+        self.last_tick = 0;
+        self.version += 1;
+    }
+    // NOTE: A tick going backward means a new attempt, so the log starts over
+    pub fn record(&mut self, tick: u32, events: &[HitEvent]) {
+        if tick < self.last_tick {
+            self.clear();
+        }
+        self.last_tick = tick;
+        for event in events {
+            let line = match event {
+                HitEvent::Bounce { ball, opponent } => {
+                    *self
+                        .hit_counts
+                        .entry((*ball, HitOpponent::Ball(*opponent)))
+                        .or_default() += 1;
+                    format!("{:0>4} MOVE #{} BY #{}", tick, ball, opponent)
+                }
+                HitEvent::WallBounce { ball, wall } => {
+                    *self
+                        .hit_counts
+                        .entry((*ball, HitOpponent::Wall(*wall)))
+                        .or_default() += 1;
+                    format!("{:0>4} WALL #{} BY W{}", tick, ball, wall)
+                }
+                HitEvent::Success { ball } => format!("{:0>4} SUCCESS #{}", tick, ball),
+                HitEvent::Failure { ball } => format!("{:0>4} FAILURE #{}", tick, ball),
+            };
+            self.log.push_back(line);
+            if self.log.len() > MAX_LOG_LINES {
+                self.log.pop_front();
+            }
+        }
+        if !events.is_empty() {
+            self.version += 1;
+        }
+    }
+    fn hit_text(&self, ball: usize) -> String {
+        let mut entries: Vec<(HitOpponent, u32)> = self
+            .hit_counts
+            .iter()
+            .filter(|((index, _), _)| *index == ball)
+            .map(|((_, opponent), count)| (*opponent, *count))
+            .collect();
+        entries.sort_by_key(|(opponent, _)| match opponent {
+            HitOpponent::Ball(index) => (0, *index),
+            HitOpponent::Wall(index) => (1, *index),
+        });
+        entries
+            .iter()
+            .map(|(opponent, count)| match opponent {
+                HitOpponent::Ball(index) => format!("#{}x{}", index, count),
+                HitOpponent::Wall(index) => format!("W{}x{}", index, count),
+            })
+            .collect::<Vec<String>>()
+            .join(" ")
+    }
+}
+
+#[derive(Component)]
+pub struct DebugOverlayShape;
+
+#[derive(Component)]
+pub struct DebugBallLabel(usize);
+
+#[derive(Component)]
+pub struct DebugVectors;
+
+#[derive(Component)]
+pub struct DebugRadii;
+
+#[derive(Component)]
+pub struct DebugLog;
+
+pub fn handle_debug_toggle(
+    key_input: Res<ButtonInput<KeyCode>>,
+    mut debug_overlay: ResMut<DebugOverlay>,
+) {
+    if key_input.just_pressed(DEBUG_TOGGLE_KEY) {
+        debug_overlay.toggle();
+    }
+}
+
+// NOTE: The balls are drawn from the simulation, before the launch it is built from the controls
+// like the aim guide. The shapes are spawned once and then updated in place.
+#[allow(clippy::too_many_arguments)]
+pub fn refresh_debug_overlay(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    cover_query: Query<Entity, With<GameCover>>,
+    shape_query: Query<Entity, With<DebugOverlayShape>>,
+    mut label_query: Query<(Entity, &DebugBallLabel, &mut Text, &mut Transform)>,
+    mut log_query: Query<&mut Text, (With<DebugLog>, Without<DebugBallLabel>)>,
+    mut vector_query: Query<&mut Path, With<DebugVectors>>,
+    mut radius_query: Query<&mut Path, (With<DebugRadii>, Without<DebugVectors>)>,
+    window_query: Query<&Window>,
+    game_status: Res<GameStatus>,
+    mut debug_overlay: ResMut<DebugOverlay>,
+    mut last_key: Local<Option<(String, ControlValues, u32, u32)>>,
+) {
+    // NOTE: Hidden in the demo and behind the pause overlay
+    let is_shown = debug_overlay.is_enabled
+        && game_status.mode != StatusMode::Demo
+        && game_status.resume_mode.is_none();
+    if last_key
+        .as_ref()
+        .map_or(false, |(code, ..)| *code != game_status.current_level.code)
+    {
+        debug_overlay.clear();
+    }
+    let overlay_key = Some((
+        game_status.current_level.code.clone(),
+        game_status.control_values.clone(),
+        game_status.simulation.time(),
+        debug_overlay.version,
+    ));
+    if is_shown && *last_key == overlay_key {
+        return;
+    }
+    if !is_shown {
+        for entity in shape_query.iter() {
+            commands.entity(entity).despawn_recursive();
+        }
+        *last_key = None;
+        return;
+    }
+    let Ok(cover_entity) = cover_query.get_single() else {
+        return;
+    };
+    *last_key = overlay_key;
+    let window = window_query.single();
+    let built;
+    let simulation =
+        if game_status.mode == StatusMode::Setup || game_status.mode == StatusMode::Deploying {
+            built = Simulation::new(&game_status.current_level, &game_status.control_values);
+            &built
+        } else {
+            &game_status.simulation
+        };
+    let mut vector_builder = PathBuilder::new();
+    let mut radius_builder = GeometryBuilder::new();
+    let mut labels: Vec<(String, Vec3)> = vec![];
+    for (index, ball) in simulation.balls().iter().enumerate() {
+        let property = &ball.property;
+        if property.v != Vec2::ZERO {
+            vector_builder.move_to(property.pos);
+            vector_builder.line_to(property.pos + property.v * VECTOR_SCALE);
+        }
+        if property.movement_type == BallMovementType::FixedReversed {
+            radius_builder = radius_builder.add(&shapes::Circle {
+                radius: property.radius,
+                center: property.pos,
+            });
+        }
+        let mut text = format!("#{} M {:.1}", index, property.mass());
+        let hits = debug_overlay.hit_text(index);
+        if !hits.is_empty() {
+            text = format!("{}\nTOTAL {}", text, hits);
+        }
+        let pos = property.pos + Vec2::splat(property.radius.min(DEBUG_GAP * 2.0));
+        labels.push((text, pos.extend(0.02)));
+    }
+    let log = debug_overlay
+        .log
+        .iter()
+        .cloned()
+        .collect::<Vec<String>>()
+        .join("\n");
+    let text_style = TextStyle {
+        font: asset_server.load(theme::FONT),
+        font_size: DEBUG_FS,
+        color: theme::FG_COLOR,
+    };
+    let mut vector_path = Some(vector_builder.build());
+    if let Ok(mut path) = vector_query.get_single_mut() {
+        *path = vector_path.take().unwrap();
+    }
+    let mut radius_path = Some(radius_builder.build());
+    if let Ok(mut path) = radius_query.get_single_mut() {
+        *path = radius_path.take().unwrap();
+    }
+    let mut log_text = Some(log);
+    if let Ok(mut text) = log_query.get_single_mut() {
+        text.sections[0].value = log_text.take().unwrap();
+    }
+    for (entity, label, mut text, mut transform) in label_query.iter_mut() {
+        match labels.get_mut(label.0) {
+            Some((label_text, pos)) => {
+                text.sections[0].value = std::mem::take(label_text);
+                transform.translation = *pos;
+            }
+            None => commands.entity(entity).despawn_recursive(),
+        }
+    }
+    let existing_labels: Vec<usize> = label_query.iter().map(|(_, label, ..)| label.0).collect();
+    commands.entity(cover_entity).with_children(|parent| {
+        for (index, (text, pos)) in labels.into_iter().enumerate() {
+            if existing_labels.contains(&index) {
+                continue;
+            }
+            parent.spawn((
+                Text2dBundle {
+                    text: Text::from_section(text, text_style.clone()),
+                    text_anchor: bevy::sprite::Anchor::BottomLeft,
+                    transform: Transform::from_translation(pos),
+                    ..default()
+                },
+                DebugBallLabel(index),
+                DebugOverlayShape,
+            ));
+        }
+        if let Some(path) = vector_path {
+            parent.spawn((
+                ShapeBundle {
+                    path,
+                    spatial: SpatialBundle {
+                        transform: Transform::from_xyz(0.0, 0.0, 0.01),
+                        ..default()
+                    },
+                    ..default()
+                },
+                Stroke::new(theme::HIGHLIGHT_COLOR, DEBUG_LINE_W),
+                DebugVectors,
+                DebugOverlayShape,
+            ));
+        }
+        if let Some(path) = radius_path {
+            parent.spawn((
+                ShapeBundle {
+                    path,
+                    spatial: SpatialBundle {
+                        transform: Transform::from_xyz(0.0, 0.0, 0.01),
+                        ..default()
+                    },
+                    ..default()
+                },
+                Stroke::new(theme::FAILURE_COLOR, DEBUG_LINE_W),
+                DebugRadii,
+                DebugOverlayShape,
+            ));
+        }
+        if let Some(log) = log_text {
+            parent.spawn((
+                Text2dBundle {
+                    text: Text::from_section(
+                        log,
+                        TextStyle {
+                            color: theme::SECONDARY_COLOR,
+                            ..text_style
+                        },
+                    )
+                    .with_justify(JustifyText::Right),
+                    text_anchor: bevy::sprite::Anchor::BottomRight,
+                    transform: Transform::from_xyz(
+                        window.width() / 2.0 - DEBUG_GAP,
+                        -window.height() / 2.0 + DEBUG_GAP,
+                        0.02,
+                    ),
+                    ..default()
+                },
+                DebugLog,
+                DebugOverlayShape,
+            ));
+        }
+    });
+}
@@ -0,0 +1,924 @@
+use super::{
+    level_builder::{LevelBallConfig, LevelConfig, LevelLoaderError, LevelSaverError, LEVEL_EXT},
+    wall::Wall,
+    *,
+};
+use crate::app::{self, cursor::AppCursorData, theme, ui};
+use bevy::input::{
+    keyboard::{Key, KeyboardInput},
+    ButtonState,
+};
+use bevy_prototype_lyon::prelude::*;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+pub const EDITOR_LEVEL_CODE: &str = "editor";
+const EDITOR_FOLDER: &str = "levels";
+const MAX_UNDO: usize = 100;
+const DEFAULT_RADIUS: f32 = FIELD_GRID_SIZE * 2.0;
+const MIN_RADIUS: f32 = FIELD_GRID_SIZE * 0.5;
+// NOTE: Velocities and forces are drawn as vectors of this many pixels per unit
+const VECTOR_SCALE: f32 = 10.0;
+const DEFAULT_POS_RANGE: f32 = FIELD_GRID_SIZE * 4.0;
+const DEFAULT_FORCE_RANGE: (f32, f32) = (4.0, 12.0);
+const DEFAULT_ANGLE_SPREAD: f32 = 45.0;
+const MAX_TEXT_LEN: usize = 24;
+const HANDLE_R: f32 = ui::FONT_SIZE / 4.0;
+const VIEW_LINE_W: f32 = ui::FONT_SIZE / 18.0;
+const VIEW_Z_LAYER: f32 = 0.5;
+
+const BALL_TYPES: [BallType; 4] = [
+    BallType::Stone,
+    BallType::Goal,
+    BallType::Bomb,
+    BallType::Attractor,
+];
+const MOVEMENT_TYPES: [BallMovementType; 3] = [
+    BallMovementType::Movable,
+    BallMovementType::Fixed,
+    BallMovementType::FixedReversed,
+];
+
+#[derive(Component)]
+pub struct EditorView;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum EditorHandle {
+    Move(Vec2),
+    Resize,
+    Velocity,
+    PosRangeMin,
+    PosRangeMax,
+    LaunchMin,
+    LaunchMax,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EditorTextField {
+    Code,
+    Name,
+}
+
+#[derive(Debug)]
+struct EditorTextEdit {
+    field: EditorTextField,
+    before: LevelConfig,
+}
+
+#[derive(Debug)]
+struct EditorDrag {
+    handle: EditorHandle,
+    before: LevelConfig,
+    cursor: Vec2,
+    is_changed: bool,
+}
+
+#[derive(Resource, Debug)]
+pub struct EditorStatus {
+    pub level: LevelConfig,
+    pub selected: Option<usize>,
+    pub ball_type: BallType,
+    pub movement_type: BallMovementType,
+    pub is_snapped: bool,
+    pub message: String,
+    pub test_handle: Option<Handle<LevelConfig>>,
+    undo_list: Vec<LevelConfig>,
+    redo_list: Vec<LevelConfig>,
+    drag: Option<EditorDrag>,
+    text_edit: Option<EditorTextEdit>,
+    version: u32,
+}
+
+impl Default for EditorStatus {
+    fn default() -> Self {
+        Self {
+            level: new_level(),
+            selected: None,
+            ball_type: BallType::Stone,
+            movement_type: BallMovementType::Fixed,
+            is_snapped: true,
+            message: String::new(),
+            test_handle: None,
+            undo_list: vec![],
+            redo_list: vec![],
+            drag: None,
+            text_edit: None,
+            version: 0,
+        }
+    }
+}
+
+impl EditorStatus {
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+    pub fn can_undo(&self) -> bool {
+        !self.undo_list.is_empty()
+    }
+    pub fn can_redo(&self) -> bool {
+        !self.redo_list.is_empty()
+    }
+    pub fn open(&mut self, level: LevelConfig) {
+        self.push_undo(self.level.clone());
+        self.level = level;
+        self.selected = None;
+        self.touch();
+    }
+    pub fn undo(&mut self) {
+        if let Some(level) = self.undo_list.pop() {
+            self.redo_list
+                .push(std::mem::replace(&mut self.level, level));
+            self.fix_selection();
+            self.touch();
+        }
+    }
+    pub fn redo(&mut self) {
+        if let Some(level) = self.redo_list.pop() {
+            self.undo_list
+                .push(std::mem::replace(&mut self.level, level));
+            self.fix_selection();
+            self.touch();
+        }
+    }
+    pub fn toggle_snap(&mut self) {
+        self.is_snapped = !self.is_snapped;
+        self.touch();
+    }
+    pub fn select(&mut self, selected: Option<usize>) {
+        self.selected = selected;
+        self.touch();
+    }
+    pub fn select_next(&mut self) {
+        let count = self.level.balls.len();
+        if count > 0 {
+            self.select(Some(self.selected.map_or(0, |index| (index + 1) % count)));
+        }
+    }
+    // NOTE: The chosen types are used by new balls and applied to the selected ball
+    pub fn set_ball_type(&mut self, ball_type: BallType) {
+        self.ball_type = ball_type.clone();
+        self.edit_selected(|ball| {
+            if ball_type != BallType::Attractor {
+                ball.well = None;
+            }
+            ball.ball_type = ball_type;
+        });
+        self.touch();
+    }
+    pub fn set_movement_type(&mut self, movement_type: BallMovementType) {
+        self.movement_type = movement_type.clone();
+        self.edit_selected(|ball| {
+            if movement_type != BallMovementType::Movable {
+                ball.property.v = Vec2::ZERO;
+                ball.control_params.force = None;
+                ball.control_params.angle = None;
+            }
+            ball.property.movement_type = movement_type;
+        });
+        self.touch();
+    }
+    pub fn cycle_movement_type(&mut self) {
+        let index = MOVEMENT_TYPES
+            .iter()
+            .position(|movement_type| *movement_type == self.movement_type)
+            .unwrap_or(0);
+        self.set_movement_type(MOVEMENT_TYPES[(index + 1) % MOVEMENT_TYPES.len()].clone());
+    }
+    pub fn text_field(&self) -> Option<EditorTextField> {
+        self.text_edit.as_ref().map(|text_edit| text_edit.field)
+    }
+    // NOTE: The typing of a field is a single undo step
+    pub fn start_text_edit(&mut self, field: EditorTextField) {
+        self.finish_text_edit();
+        self.text_edit = Some(EditorTextEdit {
+            field,
+            before: self.level.clone(),
+        });
+        self.touch();
+    }
+    pub fn finish_text_edit(&mut self) -> Option<EditorTextField> {
+        let text_edit = self.text_edit.take()?;
+        if text_edit.before.code != self.level.code || text_edit.before.name != self.level.name {
+            self.push_undo(text_edit.before);
+        }
+        self.touch();
+        Some(text_edit.field)
+    }
+    // NOTE: Codes are file names, so only lowercase letters, digits and underscores are kept
+    pub fn type_text(&mut self, text: &str) {
+        let Some(field) = self.text_field() else {
+            return;
+        };
+        let value = match field {
+            EditorTextField::Code => &mut self.level.code,
+            EditorTextField::Name => &mut self.level.name,
+        };
+        for c in text.chars() {
+            if value.chars().count() >= MAX_TEXT_LEN {
+                break;
+            }
+            match field {
+                EditorTextField::Code if c.is_ascii_alphanumeric() || c == '_' => {
+                    value.push(c.to_ascii_lowercase())
+                }
+                EditorTextField::Name if c.is_ascii_alphanumeric() || c == ' ' => {
+                    value.push(c.to_ascii_uppercase())
+                }
+                _ => {}
+            }
+        }
+        self.touch();
+    }
+    pub fn erase_text(&mut self) {
+        match self.text_field() {
+            Some(EditorTextField::Code) => self.level.code.pop(),
+            Some(EditorTextField::Name) => self.level.name.pop(),
+            None => return,
+        };
+        self.touch();
+    }
+    pub fn delete_selected(&mut self) {
+        let Some(selected) = self.selected else {
+            return;
+        };
+        self.edit(|level| {
+            level.balls.remove(selected);
+        });
+        self.selected = None;
+    }
+    // NOTE: Toggle a position range around the ball, or remove it if there is one
+    pub fn toggle_pos_range(&mut self) {
+        self.edit_selected(|ball| {
+            let params = &mut ball.control_params;
+            if params.x.is_some() || params.y.is_some() {
+                params.x = None;
+                params.y = None;
+            } else {
+                let pos = ball.property.pos;
+                params.x = Some((pos.x - DEFAULT_POS_RANGE, pos.x + DEFAULT_POS_RANGE));
+                params.y = Some((pos.y - DEFAULT_POS_RANGE, pos.y + DEFAULT_POS_RANGE));
+            }
+        });
+    }
+    // NOTE: Only movable balls can be launched
+    pub fn toggle_launch_range(&mut self) {
+        self.edit_selected(|ball| {
+            let params = &mut ball.control_params;
+            if params.force.is_some() || params.angle.is_some() {
+                params.force = None;
+                params.angle = None;
+            } else if ball.property.movement_type == BallMovementType::Movable {
+                let v = ball.property.v;
+                let angle = if v == Vec2::ZERO {
+                    0.0
+                } else {
+                    v.y.atan2(v.x).to_degrees()
+                };
+                params.force = Some(DEFAULT_FORCE_RANGE);
+                params.angle = Some((angle - DEFAULT_ANGLE_SPREAD, angle + DEFAULT_ANGLE_SPREAD));
+            }
+        });
+    }
+    pub fn test_level(&self) -> LevelConfig {
+        LevelConfig {
+            code: String::from(EDITOR_LEVEL_CODE),
+            ..self.level.clone()
+        }
+    }
+    fn insert_ball(&mut self, pos: Vec2) {
+        let pos = self.snap(pos);
+        self.level.balls.push(LevelBallConfig {
+            ball_type: self.ball_type.clone(),
+            property: BallProperty::new(pos, DEFAULT_RADIUS, self.movement_type.clone()),
+            control_params: BallControlParams::default(),
+            well: None,
+        });
+        self.selected = Some(self.level.balls.len() - 1);
+        self.touch();
+    }
+    fn snap(&self, pos: Vec2) -> Vec2 {
+        if self.is_snapped {
+            (pos / FIELD_GRID_SIZE).round() * FIELD_GRID_SIZE
+        } else {
+            pos
+        }
+    }
+    fn edit(&mut self, action: impl FnOnce(&mut LevelConfig)) {
+        self.push_undo(self.level.clone());
+        action(&mut self.level);
+        self.touch();
+    }
+    fn edit_selected(&mut self, action: impl FnOnce(&mut LevelBallConfig)) {
+        let Some(selected) = self.selected else {
+            return;
+        };
+        if selected < self.level.balls.len() {
+            self.edit(|level| action(&mut level.balls[selected]));
+        }
+    }
+    fn push_undo(&mut self, level: LevelConfig) {
+        self.undo_list.push(level);
+        if self.undo_list.len() > MAX_UNDO {
+            self.undo_list.remove(0);
+        }
+        self.redo_list.clear();
+    }
+    fn fix_selection(&mut self) {
+        if self
+            .selected
+            .map_or(false, |index| index >= self.level.balls.len())
+        {
+            self.selected = None;
+        }
+    }
+    fn touch(&mut self) {
+        self.version = self.version.wrapping_add(1);
+    }
+    fn handle_at(&self, pos: Vec2) -> Option<EditorHandle> {
+        let ball = self.level.balls.get(self.selected?)?;
+        let center = ball.property.pos;
+        let mut handles = vec![(EditorHandle::Resize, resize_handle_pos(ball))];
+        if ball.property.movement_type == BallMovementType::Movable {
+            handles.push((
+                EditorHandle::Velocity,
+                center + ball.property.v * VECTOR_SCALE,
+            ));
+        }
+        if let Some((min, max)) = pos_range_corners(ball) {
+            handles.push((EditorHandle::PosRangeMin, min));
+            handles.push((EditorHandle::PosRangeMax, max));
+        }
+        if let Some((min, max)) = launch_handles(ball) {
+            handles.push((EditorHandle::LaunchMin, min));
+            handles.push((EditorHandle::LaunchMax, max));
+        }
+        handles
+            .into_iter()
+            .find(|(_, handle_pos)| handle_pos.distance(pos) <= HANDLE_R * 2.0)
+            .map(|(handle, _)| handle)
+    }
+    // NOTE: Pick the smallest ball under the cursor, so balls inside a reversed one can be chosen
+    fn ball_at(&self, pos: Vec2) -> Option<usize> {
+        self.level
+            .balls
+            .iter()
+            .enumerate()
+            .filter(|(_, ball)| ball.property.pos.distance(pos) <= ball.property.radius)
+            .min_by(|(_, a), (_, b)| a.property.radius.total_cmp(&b.property.radius))
+            .map(|(index, _)| index)
+    }
+    // NOTE: A new ball and the drag which places it are a single undo step
+    fn start_drag(&mut self, pos: Vec2) {
+        self.finish_text_edit();
+        let before = self.level.clone();
+        let (handle, is_changed) = if let Some(handle) = self.handle_at(pos) {
+            (handle, false)
+        } else if let Some(index) = self.ball_at(pos) {
+            self.select(Some(index));
+            let offset = self.snap(pos) - self.level.balls[index].property.pos;
+            (EditorHandle::Move(offset), false)
+        } else {
+            self.insert_ball(pos);
+            (EditorHandle::Move(Vec2::ZERO), true)
+        };
+        self.drag = Some(EditorDrag {
+            handle,
+            before,
+            cursor: pos,
+            is_changed,
+        });
+    }
+    fn move_drag(&mut self, pos: Vec2) {
+        let Some(drag) = self.drag.as_mut() else {
+            return;
+        };
+        if drag.cursor == pos {
+            return;
+        }
+        drag.cursor = pos;
+        let handle = drag.handle;
+        self.drag_to(handle, pos);
+    }
+    fn finish_drag(&mut self) {
+        if let Some(drag) = self.drag.take() {
+            if drag.is_changed {
+                self.push_undo(drag.before);
+            }
+        }
+    }
+    fn drag_to(&mut self, handle: EditorHandle, pos: Vec2) {
+        let Some(selected) = self.selected else {
+            return;
+        };
+        let snapped = self.snap(pos);
+        let Some(ball) = self.level.balls.get_mut(selected) else {
+            return;
+        };
+        let center = ball.property.pos;
+        match handle {
+            EditorHandle::Move(offset) => {
+                let target = snapped - offset;
+                let delta = target - center;
+                ball.property.pos = target;
+                // NOTE: Position ranges follow the ball
+                let params = &mut ball.control_params;
+                params.x = params.x.map(|(min, max)| (min + delta.x, max + delta.x));
+                params.y = params.y.map(|(min, max)| (min + delta.y, max + delta.y));
+            }
+            EditorHandle::Resize => {
+                let radius = snapped.distance(center);
+                ball.property.radius = radius.max(MIN_RADIUS);
+            }
+            EditorHandle::Velocity => {
+                ball.property.v = (snapped - center) / VECTOR_SCALE;
+            }
+            EditorHandle::PosRangeMin | EditorHandle::PosRangeMax => {
+                let is_min = handle == EditorHandle::PosRangeMin;
+                let params = &mut ball.control_params;
+                params.x = params.x.map(|range| update_range(range, snapped.x, is_min));
+                params.y = params.y.map(|range| update_range(range, snapped.y, is_min));
+            }
+            EditorHandle::LaunchMin | EditorHandle::LaunchMax => {
+                let is_min = handle == EditorHandle::LaunchMin;
+                let offset = pos - center;
+                let angle = offset.y.atan2(offset.x).to_degrees();
+                let force = offset.length() / VECTOR_SCALE;
+                let params = &mut ball.control_params;
+                params.angle = params.angle.map(|range| update_range(range, angle, is_min));
+                params.force = params
+                    .force
+                    .map(|range| update_range(range, force.max(0.0), is_min));
+            }
+        }
+        if let Some(drag) = self.drag.as_mut() {
+            drag.is_changed = true;
+        }
+        self.touch();
+    }
+}
+
+pub fn new_level() -> LevelConfig {
+    let stone_pos = Vec2::new(-200.0, 0.0);
+    let mut stone = BallProperty::new(stone_pos, 15.0, BallMovementType::Movable);
+    stone.v = Vec2::new(8.0, 0.0);
+    LevelConfig {
+        code: String::from("custom"),
+        name: String::from("CUSTOM"),
+        balls: vec![
+            LevelBallConfig {
+                ball_type: BallType::Stone,
+                property: BallProperty::new(Vec2::ZERO, 320.0, BallMovementType::FixedReversed),
+                control_params: BallControlParams::default(),
+                well: None,
+            },
+            LevelBallConfig {
+                ball_type: BallType::Stone,
+                property: stone,
+                control_params: BallControlParams {
+                    force: Some(DEFAULT_FORCE_RANGE),
+                    angle: Some((-DEFAULT_ANGLE_SPREAD, DEFAULT_ANGLE_SPREAD)),
+                    ..default()
+                },
+                well: None,
+            },
+            LevelBallConfig {
+                ball_type: BallType::Goal,
+                property: BallProperty::new(Vec2::new(200.0, 0.0), 30.0, BallMovementType::Fixed),
+                control_params: BallControlParams::default(),
+                well: None,
+            },
+        ],
+        ..default()
+    }
+}
+
+pub fn ball_type_at(index: usize) -> Option<BallType> {
+    BALL_TYPES.get(index).cloned()
+}
+
+// NOTE: Edited levels are saved in the app config folder with the level code as the file name
+pub fn save_editor_level(level: &LevelConfig) -> Result<PathBuf, LevelSaverError> {
+    let level_dir = editor_dir_path();
+    fs::create_dir_all(&level_dir)?;
+    let path = level_dir.join(format!("{}.{}", level.code, LEVEL_EXT));
+    level.save(&path)?;
+    Ok(path)
+}
+
+pub fn load_editor_level(path: &Path) -> Result<LevelConfig, LevelLoaderError> {
+    let code = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .and_then(|name| name.split('.').next())
+        .unwrap_or_default();
+    LevelConfig::from_bytes(code, &fs::read(path)?)
+}
+
+pub fn saved_editor_level_path(code: &str) -> PathBuf {
+    editor_dir_path().join(format!("{}.{}", code, LEVEL_EXT))
+}
+
+fn editor_dir_path() -> PathBuf {
+    dirs::config_dir()
+        .map(|native_config_dir| native_config_dir.join(app::APP_CODE))
+        .unwrap_or(Path::new("local").join("configuration"))
+        .join(EDITOR_FOLDER)
+}
+
+pub fn handle_editing(
+    mouse_input: Res<ButtonInput<MouseButton>>,
+    cursor_data: Res<AppCursorData>,
+    interaction_query: Query<&Interaction>,
+    mut editor: ResMut<EditorStatus>,
+) {
+    let pos = cursor_data.canvas_pos;
+    if mouse_input.just_pressed(MouseButton::Left) {
+        // NOTE: Clicks on the page buttons are not edits
+        if interaction_query
+            .iter()
+            .any(|interaction| *interaction != Interaction::None)
+        {
+            return;
+        }
+        editor.start_drag(pos);
+        return;
+    }
+    if mouse_input.just_pressed(MouseButton::Right) {
+        editor.select(None);
+        return;
+    }
+    if mouse_input.just_released(MouseButton::Left) {
+        editor.finish_drag();
+        return;
+    }
+    if mouse_input.pressed(MouseButton::Left) {
+        editor.move_drag(pos);
+    }
+}
+
+pub fn handle_editor_keys(
+    key_input: Res<ButtonInput<KeyCode>>,
+    mut keyboard_events: EventReader<KeyboardInput>,
+    mut editor: ResMut<EditorStatus>,
+) {
+    if editor.drag.is_some() {
+        return;
+    }
+    // NOTE: While typing a field the keys are text, enter is left to the focused button
+    if editor.text_field().is_some() {
+        for event in keyboard_events.read() {
+            if event.state != ButtonState::Pressed {
+                continue;
+            }
+            match &event.logical_key {
+                Key::Character(text) => editor.type_text(text),
+                Key::Space => editor.type_text(" "),
+                Key::Backspace => editor.erase_text(),
+                Key::Escape => {
+                    editor.finish_text_edit();
+                }
+                _ => {}
+            }
+        }
+        return;
+    }
+    keyboard_events.clear();
+    let is_ctrl = key_input.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]);
+    let is_shift = key_input.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+    if is_ctrl && key_input.just_pressed(KeyCode::KeyZ) {
+        if is_shift {
+            editor.redo();
+        } else {
+            editor.undo();
+        }
+    } else if is_ctrl && key_input.just_pressed(KeyCode::KeyY) {
+        editor.redo();
+    } else if key_input.any_just_pressed([KeyCode::Delete, KeyCode::Backspace]) {
+        editor.delete_selected();
+    } else if key_input.just_pressed(KeyCode::KeyG) {
+        editor.toggle_snap();
+    } else if key_input.just_pressed(KeyCode::KeyM) {
+        editor.cycle_movement_type();
+    } else if key_input.just_pressed(KeyCode::KeyX) {
+        editor.toggle_pos_range();
+    } else if key_input.just_pressed(KeyCode::KeyL) {
+        editor.toggle_launch_range();
+    } else if key_input.just_pressed(KeyCode::Tab) {
+        editor.select_next();
+    } else {
+        let digit_keys = [
+            KeyCode::Digit1,
+            KeyCode::Digit2,
+            KeyCode::Digit3,
+            KeyCode::Digit4,
+        ];
+        if let Some(ball_type) = digit_keys
+            .iter()
+            .position(|key| key_input.just_pressed(*key))
+            .and_then(ball_type_at)
+        {
+            editor.set_ball_type(ball_type);
+        }
+    }
+}
+
+pub fn refresh_editor_view(
+    mut commands: Commands,
+    dyn_query: Query<Entity, With<GameDyn>>,
+    view_query: Query<Entity, With<EditorView>>,
+    editor: Res<EditorStatus>,
+    mut last_version: Local<Option<u32>>,
+) {
+    if *last_version == Some(editor.version) && !view_query.is_empty() {
+        return;
+    }
+    *last_version = Some(editor.version);
+    for entity in view_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    let Ok(dyn_entity) = dyn_query.get_single() else {
+        return;
+    };
+    commands.entity(dyn_entity).with_children(|parent| {
+        parent
+            .spawn((
+                SpatialBundle {
+                    transform: Transform::from_xyz(0.0, 0.0, VIEW_Z_LAYER),
+                    ..default()
+                },
+                EditorView,
+            ))
+            .with_children(|parent| {
+                for zone in editor.level.zones.iter() {
+                    zone.create_sprite(parent);
+                }
+                for wall in editor.level.walls.iter() {
+                    Wall::create_sprite(parent, wall.clone());
+                }
+                for (index, ball) in editor.level.balls.iter().enumerate() {
+                    draw_ball(parent, ball, editor.selected == Some(index));
+                }
+                if let Some(ball) = editor
+                    .selected
+                    .and_then(|index| editor.level.balls.get(index))
+                {
+                    draw_handles(parent, ball);
+                }
+            });
+    });
+}
+
+fn draw_ball(parent: &mut ChildBuilder, ball: &LevelBallConfig, is_selected: bool) {
+    let color = ball.ball_type.color();
+    let shape = shapes::Circle {
+        radius: ball.property.radius,
+        center: ball.property.pos,
+    };
+    let fill_alpha = match ball.property.movement_type {
+        BallMovementType::FixedReversed => 0.0,
+        BallMovementType::Fixed => 0.3,
+        BallMovementType::Movable => 0.6,
+    };
+    let stroke_color = if is_selected {
+        theme::HIGHLIGHT_COLOR
+    } else {
+        color
+    };
+    let line_w = if is_selected {
+        VIEW_LINE_W * 3.0
+    } else {
+        VIEW_LINE_W
+    };
+    parent.spawn((
+        ShapeBundle {
+            path: GeometryBuilder::build_as(&shape),
+            ..default()
+        },
+        Fill::color(color.with_alpha(fill_alpha)),
+        Stroke::new(stroke_color, line_w),
+    ));
+    if ball.property.movement_type == BallMovementType::Movable && ball.property.v != Vec2::ZERO {
+        draw_line(
+            parent,
+            ball.property.pos,
+            ball.property.pos + ball.property.v * VECTOR_SCALE,
+            color,
+        );
+    }
+}
+
+fn draw_handles(parent: &mut ChildBuilder, ball: &LevelBallConfig) {
+    let center = ball.property.pos;
+    let guide_color = ball.ball_type.color().with_alpha(0.4);
+    if let Some((min, max)) = pos_range_corners(ball) {
+        let mut path_builder = PathBuilder::new();
+        path_builder.move_to(min);
+        path_builder.line_to(Vec2::new(max.x, min.y));
+        path_builder.line_to(max);
+        path_builder.line_to(Vec2::new(min.x, max.y));
+        path_builder.close();
+        parent.spawn((
+            ShapeBundle {
+                path: path_builder.build(),
+                ..default()
+            },
+            Stroke::new(guide_color, VIEW_LINE_W),
+        ));
+        draw_handle(parent, min);
+        draw_handle(parent, max);
+    }
+    if let Some((min, max)) = launch_handles(ball) {
+        let (angle_min, angle_max) = ball.control_params.angle.unwrap_or_default();
+        let (force_min, force_max) = ball.control_params.force.unwrap_or_default();
+        for angle in [angle_min, angle_max] {
+            let dir = Vec2::from_angle(angle.to_radians());
+            draw_line(
+                parent,
+                center + dir * force_min * VECTOR_SCALE,
+                center + dir * force_max * VECTOR_SCALE,
+                guide_color,
+            );
+        }
+        for force in [force_min, force_max] {
+            let mut path_builder = PathBuilder::new();
+            path_builder
+                .move_to(center + Vec2::from_angle(angle_min.to_radians()) * force * VECTOR_SCALE);
+            path_builder.arc(
+                center,
+                Vec2::splat(force * VECTOR_SCALE),
+                (angle_max - angle_min).to_radians(),
+                0.0,
+            );
+            parent.spawn((
+                ShapeBundle {
+                    path: path_builder.build(),
+                    ..default()
+                },
+                Stroke::new(guide_color, VIEW_LINE_W),
+            ));
+        }
+        draw_handle(parent, min);
+        draw_handle(parent, max);
+    }
+    draw_handle(parent, resize_handle_pos(ball));
+    if ball.property.movement_type == BallMovementType::Movable {
+        draw_handle(parent, center + ball.property.v * VECTOR_SCALE);
+    }
+}
+
+fn draw_handle(parent: &mut ChildBuilder, pos: Vec2) {
+    let shape = shapes::Circle {
+        radius: HANDLE_R,
+        center: pos,
+    };
+    parent.spawn((
+        ShapeBundle {
+            path: GeometryBuilder::build_as(&shape),
+            spatial: SpatialBundle {
+                transform: Transform::from_xyz(0.0, 0.0, 0.1),
+                ..default()
+            },
+            ..default()
+        },
+        Fill::color(theme::HIGHLIGHT_COLOR),
+    ));
+}
+
+fn draw_line(parent: &mut ChildBuilder, start: Vec2, end: Vec2, color: Color) {
+    let mut path_builder = PathBuilder::new();
+    path_builder.move_to(start);
+    path_builder.line_to(end);
+    parent.spawn((
+        ShapeBundle {
+            path: path_builder.build(),
+            ..default()
+        },
+        Stroke {
+            color,
+            options: StrokeOptions::DEFAULT
+                .with_line_width(VIEW_LINE_W)
+                .with_line_cap(LineCap::Round),
+        },
+    ));
+}
+
+fn resize_handle_pos(ball: &LevelBallConfig) -> Vec2 {
+    ball.property.pos + Vec2::new(ball.property.radius, 0.0)
+}
+
+fn pos_range_corners(ball: &LevelBallConfig) -> Option<(Vec2, Vec2)> {
+    let params = &ball.control_params;
+    if params.x.is_none() && params.y.is_none() {
+        return None;
+    }
+    let pos = ball.property.pos;
+    let (x_min, x_max) = params.x.unwrap_or((pos.x, pos.x));
+    let (y_min, y_max) = params.y.unwrap_or((pos.y, pos.y));
+    Some((Vec2::new(x_min, y_min), Vec2::new(x_max, y_max)))
+}
+
+fn launch_handles(ball: &LevelBallConfig) -> Option<(Vec2, Vec2)> {
+    let params = &ball.control_params;
+    let (force_min, force_max) = params.force?;
+    let (angle_min, angle_max) = params.angle?;
+    let center = ball.property.pos;
+    Some((
+        center + Vec2::from_angle(angle_min.to_radians()) * force_min * VECTOR_SCALE,
+        center + Vec2::from_angle(angle_max.to_radians()) * force_max * VECTOR_SCALE,
+    ))
+}
+
+// NOTE: Keep ranges valid by pushing the other end along
+fn update_range(range: (f32, f32), value: f32, is_min: bool) -> (f32, f32) {
+    if is_min {
+        (value, range.1.max(value))
+    } else {
+        (range.0.min(value), value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn undo_keeps_the_latest_steps() {
+        let mut editor = EditorStatus::default();
+        editor.select(Some(2));
+        for radius in 0..MAX_UNDO + 10 {
+            editor.edit_selected(|ball| ball.property.radius = radius as f32 + 1.0);
+        }
+        let mut steps = 0;
+        while editor.can_undo() {
+            editor.undo();
+            steps += 1;
+        }
+        assert_eq!(steps, MAX_UNDO);
+        assert_eq!(editor.level.balls[2].property.radius, 10.0);
+    }
+
+    #[test]
+    fn new_edit_clears_redo() {
+        let mut editor = EditorStatus::default();
+        editor.select(Some(2));
+        editor.edit_selected(|ball| ball.property.radius = 40.0);
+        editor.undo();
+        assert!(editor.can_redo());
+        editor.redo();
+        assert_eq!(editor.level.balls[2].property.radius, 40.0);
+        editor.undo();
+        editor.edit_selected(|ball| ball.property.radius = 50.0);
+        assert!(!editor.can_redo());
+    }
+
+    #[test]
+    fn insert_and_drag_is_one_undo_step() {
+        let mut editor = EditorStatus::default();
+        let before = editor.level.balls.len();
+        editor.start_drag(Vec2::new(0.0, 400.0));
+        editor.move_drag(Vec2::new(40.0, 400.0));
+        editor.move_drag(Vec2::new(80.0, 400.0));
+        editor.finish_drag();
+        assert_eq!(editor.level.balls.len(), before + 1);
+        assert_eq!(
+            editor.level.balls[before].property.pos,
+            editor.snap(Vec2::new(80.0, 400.0))
+        );
+        editor.undo();
+        assert_eq!(editor.level.balls.len(), before);
+        assert!(!editor.can_undo());
+        // NOTE: A click on a ball without moving it is not an edit
+        editor.start_drag(Vec2::new(200.0, 0.0));
+        editor.finish_drag();
+        assert!(!editor.can_undo());
+    }
+
+    #[test]
+    fn ranges_keep_min_below_max() {
+        assert_eq!(update_range((0.0, 10.0), 5.0, true), (5.0, 10.0));
+        assert_eq!(update_range((0.0, 10.0), 15.0, true), (15.0, 15.0));
+        assert_eq!(update_range((0.0, 10.0), -5.0, false), (-5.0, -5.0));
+        assert_eq!(update_range((0.0, 10.0), 5.0, false), (0.0, 5.0));
+    }
+
+    #[test]
+    fn typing_filters_the_field() {
+        let mut editor = EditorStatus::default();
+        editor.type_text("ignored");
+        assert_eq!(editor.level.code, "custom");
+        editor.start_text_edit(EditorTextField::Code);
+        editor.type_text("_Lv-2 ./x");
+        assert_eq!(editor.level.code, "custom_lv2x");
+        editor.start_text_edit(EditorTextField::Name);
+        editor.type_text(" no.1!");
+        assert_eq!(editor.level.name, "CUSTOM NO1");
+        editor.type_text(&"a".repeat(MAX_TEXT_LEN));
+        assert_eq!(editor.level.name.chars().count(), MAX_TEXT_LEN);
+        editor.finish_text_edit();
+        editor.undo();
+        assert_eq!(editor.level.name, "CUSTOM");
+        assert_eq!(editor.level.code, "custom_lv2x");
+    }
+}
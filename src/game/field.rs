@@ -13,6 +13,7 @@ pub fn refresh_field(
     bg_query: Query<Entity, With<GameBg>>,
     fg_query: Query<Entity, With<GameFg>>,
     window_query: Query<&Window>,
+    time_scale: Res<TimeScale>,
     mut game_status: ResMut<GameStatus>,
 ) {
     if !game_status.is_refreshed(ui::REFRESH_GAME_BG) {
@@ -29,13 +30,19 @@ pub fn refresh_field(
         let mut entity_commands = commands.get_entity(fg_entity).unwrap();
         entity_commands.despawn_descendants();
         entity_commands.with_children(|parent| {
-            draw_game_fg(parent, &window_query, &asset_server, &game_status);
+            draw_game_fg(
+                parent,
+                &window_query,
+                &asset_server,
+                &game_status,
+                &time_scale,
+            );
         });
         game_status.set_refreshed(ui::REFRESH_GAME_FG);
     }
 }
 
-const FIELD_GRID_SIZE: f32 = ui::FONT_SIZE * 0.5;
+pub const FIELD_GRID_SIZE: f32 = ui::FONT_SIZE * 0.5;
 
 fn draw_grid_bg(parent: &mut ChildBuilder, window_query: &Query<&Window>) {
     let window = window_query.get_single().unwrap();
@@ -159,6 +166,7 @@ fn draw_game_fg(
     window_query: &Query<&Window>,
     asset_server: &Res<AssetServer>,
     game_status: &ResMut<GameStatus>,
+    time_scale: &TimeScale,
 ) {
     let window = window_query.single();
     let win_w = window.resolution.width();
@@ -279,7 +287,7 @@ fn draw_game_fg(
                 let ball_type_str: String;
                 let control_num_color: Color;
                 if let Some(ball_type) = &control.ball_type {
-                    ball_type_str = ball_type.to_string();
+                    ball_type_str = ball_type.control_icon_set();
                     control_num_color = ball_type.color();
                 } else {
                     ball_type_str = String::from("demo");
@@ -453,4 +461,64 @@ fn draw_game_fg(
                 ));
             });
     }
+    if game_status.replay.is_some() || !time_scale.is_normal() {
+        draw_run_hud(
+            parent,
+            asset_server,
+            game_status,
+            time_scale,
+            Vec3::new(win_w / 2.0 - LV_CONTROL_GAP, lv_y_base, lv_z),
+        );
+    }
+}
+
+fn draw_run_hud(
+    parent: &mut ChildBuilder,
+    asset_server: &Res<AssetServer>,
+    game_status: &ResMut<GameStatus>,
+    time_scale: &TimeScale,
+    pos: Vec3,
+) {
+    let title = if game_status.replay.is_some() {
+        "REPLAY"
+    } else {
+        "TIME"
+    };
+    let mut lines = vec![(
+        format!("{} x{:.2}", title, time_scale.scale()),
+        theme::HIGHLIGHT_COLOR,
+    )];
+    if time_scale.is_stopped() {
+        lines.push((String::from("STOPPED"), theme::SECONDARY_COLOR));
+    }
+    if let Some(replay) = &game_status.replay {
+        if let Some(divergence) = &replay.divergence {
+            lines.push((divergence.to_string(), theme::FAILURE_COLOR));
+        } else if replay.is_verified {
+            lines.push((String::from("VERIFIED"), theme::SUCCESS_COLOR));
+        }
+    }
+    parent
+        .spawn((SpatialBundle {
+            transform: Transform::from_translation(pos),
+            ..default()
+        },))
+        .with_children(|parent| {
+            for (i, (text, color)) in lines.into_iter().enumerate() {
+                parent.spawn((Text2dBundle {
+                    text: Text::from_section(
+                        text,
+                        TextStyle {
+                            font: asset_server.load(theme::FONT),
+                            font_size: LV_CONTROL_FS,
+                            color,
+                        },
+                    )
+                    .with_justify(JustifyText::Right),
+                    text_anchor: bevy::sprite::Anchor::CenterRight,
+                    transform: Transform::from_xyz(0.0, -LV_CONTROL_FS * 1.2 * i as f32, 0.001),
+                    ..default()
+                },));
+            }
+        });
 }
@@ -0,0 +1,262 @@
+use crate::app::{theme, ui};
+use bevy::prelude::*;
+use bevy_prototype_lyon::prelude::*;
+use serde::{Deserialize, Serialize};
+
+const ZONE_LINE_W: f32 = ui::FONT_SIZE / 36.0;
+const ZONE_HATCH_P: f32 = ZONE_LINE_W * 12.0;
+const ZONE_Z_LAYER: f32 = 0.1;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ZoneShape {
+    Rect { center: Vec2, size: Vec2 },
+    Circle { center: Vec2, radius: f32 },
+}
+
+impl ZoneShape {
+    pub fn contains(&self, pos: Vec2) -> bool {
+        match self {
+            ZoneShape::Rect { center, size } => {
+                let offset = (pos - *center).abs();
+                offset.x <= size.x * 0.5 && offset.y <= size.y * 0.5
+            }
+            ZoneShape::Circle { center, radius } => {
+                pos.distance_squared(*center) <= radius * radius
+            }
+        }
+    }
+    fn center(&self) -> Vec2 {
+        match self {
+            ZoneShape::Rect { center, .. } | ZoneShape::Circle { center, .. } => *center,
+        }
+    }
+    // NOTE: The chord of the hatch line which is c away from the center, in the local space
+    fn hatch_chord(&self, c: f32) -> Option<(Vec2, Vec2)> {
+        let d = Vec2::new(1.0, 1.0).normalize();
+        let n = d.perp();
+        let (t0, t1) = match self {
+            ZoneShape::Rect { size, .. } => {
+                let half = *size * 0.5;
+                let tx = ((-half.x - n.x * c) / d.x, (half.x - n.x * c) / d.x);
+                let ty = ((-half.y - n.y * c) / d.y, (half.y - n.y * c) / d.y);
+                (tx.0.max(ty.0), tx.1.min(ty.1))
+            }
+            ZoneShape::Circle { radius, .. } => {
+                let l = (radius * radius - c * c).sqrt();
+                (-l, l)
+            }
+        };
+        if t0.is_nan() || t0 >= t1 {
+            return None;
+        }
+        Some((n * c + d * t0, n * c + d * t1))
+    }
+    fn extent(&self) -> f32 {
+        match self {
+            ZoneShape::Rect { size, .. } => size.length() * 0.5,
+            ZoneShape::Circle { radius, .. } => *radius,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ZoneEffect {
+    Acceleration(Vec2),
+    Drag(f32),
+    SpeedCap(f32),
+}
+
+impl ZoneEffect {
+    pub fn apply(&self, v: Vec2) -> Vec2 {
+        match self {
+            ZoneEffect::Acceleration(a) => v + *a,
+            ZoneEffect::Drag(drag) => v * (1.0 - drag),
+            ZoneEffect::SpeedCap(cap) => v.clamp_length_max(*cap),
+        }
+    }
+    fn color(&self) -> Color {
+        match self {
+            ZoneEffect::Acceleration(_) => theme::HIGHLIGHT_COLOR,
+            ZoneEffect::Drag(_) => theme::SECONDARY_COLOR,
+            ZoneEffect::SpeedCap(_) => theme::CONTROL_COLOR,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ForceZone {
+    pub shape: ZoneShape,
+    pub effect: ZoneEffect,
+}
+
+impl ForceZone {
+    pub fn validate(&self) -> Result<(), &'static str> {
+        match &self.shape {
+            ZoneShape::Rect { size, .. } => {
+                // NOTE: NaN compares false both ways, so it is rejected by name
+                if size.is_nan() || size.x <= 0.0 || size.y <= 0.0 {
+                    return Err("the size of a zone should be positive");
+                }
+            }
+            ZoneShape::Circle { radius, .. } => {
+                if radius.is_nan() || *radius <= 0.0 {
+                    return Err("the radius of a zone should be positive");
+                }
+            }
+        }
+        match &self.effect {
+            ZoneEffect::Acceleration(_) => (),
+            ZoneEffect::Drag(drag) => {
+                if !(0.0..=1.0).contains(drag) {
+                    return Err("the drag of a zone should be between 0 and 1");
+                }
+            }
+            ZoneEffect::SpeedCap(cap) => {
+                if cap.is_nan() || *cap <= 0.0 {
+                    return Err("the speed cap of a zone should be positive");
+                }
+            }
+        }
+        Ok(())
+    }
+    pub fn create_sprite(&self, parent: &mut ChildBuilder) {
+        let color = self.effect.color();
+        let center = self.shape.center();
+        let mut hatch_builder = PathBuilder::new();
+        let line_count = (self.shape.extent() / ZONE_HATCH_P).floor() as i32;
+        for i in -line_count..=line_count {
+            if let Some((start, end)) = self.shape.hatch_chord(ZONE_HATCH_P * i as f32) {
+                hatch_builder.move_to(start);
+                hatch_builder.line_to(end);
+            }
+        }
+        let area_builder = match &self.shape {
+            ZoneShape::Rect { size, .. } => GeometryBuilder::new().add(&shapes::Rectangle {
+                extents: *size,
+                ..default()
+            }),
+            ZoneShape::Circle { radius, .. } => GeometryBuilder::new().add(&shapes::Circle {
+                radius: *radius,
+                center: Vec2::ZERO,
+            }),
+        };
+        parent
+            .spawn(SpatialBundle {
+                transform: Transform::from_xyz(center.x, center.y, ZONE_Z_LAYER),
+                ..default()
+            })
+            .with_children(|parent| {
+                parent.spawn((
+                    ShapeBundle {
+                        path: area_builder.build(),
+                        ..default()
+                    },
+                    Fill::color(color.with_alpha(0.1)),
+                    Stroke::new(color.with_alpha(0.5), ZONE_LINE_W),
+                ));
+                parent.spawn((
+                    ShapeBundle {
+                        path: hatch_builder.build(),
+                        spatial: SpatialBundle {
+                            transform: Transform::from_xyz(0.0, 0.0, 0.001),
+                            ..default()
+                        },
+                        ..default()
+                    },
+                    Stroke::new(color.with_alpha(0.3), ZONE_LINE_W),
+                ));
+            });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{
+        level_builder::LevelConfig,
+        simulation::{ControlValues, Simulation},
+    };
+
+    // NOTE: A stone moving right at the origin, with no gravity and a zone given in RON
+    fn v_after_one_tick(zone: &str) -> Vec2 {
+        let level = format!(
+            r#"(
+                name: "TEST",
+                gravity: (0.0, 0.0),
+                balls: [
+                    (ball_type: Stone, property: (pos: (0.0, 0.0), radius: 320.0, movement_type: FixedReversed)),
+                    (ball_type: Stone, property: (pos: (0.0, 0.0), v: (10.0, 0.0), radius: 10.0, movement_type: Movable)),
+                ],
+                zones: [{zone}],
+            )"#
+        );
+        let level = LevelConfig::from_bytes("test", level.as_bytes()).unwrap();
+        let mut simulation = Simulation::new(&level, &ControlValues::from_level(&level));
+        simulation.start();
+        simulation.step();
+        simulation.ball(1).unwrap().property.v
+    }
+
+    #[test]
+    fn shapes_contain_their_edges() {
+        let rect = ZoneShape::Rect {
+            center: Vec2::new(10.0, 20.0),
+            size: Vec2::new(40.0, 20.0),
+        };
+        assert!(rect.contains(Vec2::new(10.0, 20.0)));
+        assert!(rect.contains(Vec2::new(30.0, 30.0)));
+        assert!(!rect.contains(Vec2::new(30.1, 20.0)));
+        assert!(!rect.contains(Vec2::new(10.0, 9.9)));
+        let circle = ZoneShape::Circle {
+            center: Vec2::new(10.0, 20.0),
+            radius: 5.0,
+        };
+        assert!(circle.contains(Vec2::new(15.0, 20.0)));
+        assert!(!circle.contains(Vec2::new(14.0, 24.0)));
+    }
+
+    #[test]
+    fn effects_apply_inside_the_zone_only() {
+        let rect = "Rect(center: (0.0, 0.0), size: (100.0, 100.0))";
+        let circle = "Circle(center: (0.0, 0.0), radius: 50.0)";
+        let v = v_after_one_tick(&format!(
+            "(shape: {rect}, effect: Acceleration((0.0, 1.0)))"
+        ));
+        assert_eq!(v, Vec2::new(10.0, 1.0));
+        let v = v_after_one_tick(&format!("(shape: {circle}, effect: Drag(0.5))"));
+        assert_eq!(v, Vec2::new(5.0, 0.0));
+        let v = v_after_one_tick(&format!("(shape: {rect}, effect: SpeedCap(3.0))"));
+        assert_eq!(v, Vec2::new(3.0, 0.0));
+        let away = "Circle(center: (200.0, 0.0), radius: 50.0)";
+        let v = v_after_one_tick(&format!("(shape: {away}, effect: Drag(0.5))"));
+        assert_eq!(v, Vec2::new(10.0, 0.0));
+    }
+
+    #[test]
+    fn reject_nan_zones() {
+        let zone = |shape, effect| ForceZone { shape, effect };
+        let circle = ZoneShape::Circle {
+            center: Vec2::ZERO,
+            radius: 10.0,
+        };
+        assert!(zone(circle.clone(), ZoneEffect::SpeedCap(1.0))
+            .validate()
+            .is_ok());
+        assert!(zone(circle.clone(), ZoneEffect::SpeedCap(f32::NAN))
+            .validate()
+            .is_err());
+        assert!(zone(circle.clone(), ZoneEffect::Drag(f32::NAN))
+            .validate()
+            .is_err());
+        let nan_circle = ZoneShape::Circle {
+            center: Vec2::ZERO,
+            radius: f32::NAN,
+        };
+        assert!(zone(nan_circle, ZoneEffect::Drag(0.5)).validate().is_err());
+        let nan_rect = ZoneShape::Rect {
+            center: Vec2::ZERO,
+            size: Vec2::new(10.0, f32::NAN),
+        };
+        assert!(zone(nan_rect, ZoneEffect::Drag(0.5)).validate().is_err());
+    }
+}
@@ -0,0 +1,280 @@
+use super::{
+    level_builder::{LevelBallConfig, LevelConfig, LevelLoaderError},
+    solver::{solve, SolverConfig, SolverReport},
+    *,
+};
+use crate::app;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+const FIELD_RADIUS: f32 = 320.0;
+const FIELD_MARGIN: f32 = 10.0;
+const MIN_BALL_GAP: f32 = 20.0;
+const STONE_RADIUS: f32 = 15.0;
+const MIN_GOAL_DISTANCE: f32 = FIELD_RADIUS * 0.6;
+const PLACE_TRIES: usize = 64;
+// NOTE: The target winning fraction goes from the easy end to the hard end in log scale
+const EASY_WIN_FRACTION: f32 = 0.4;
+const HARD_WIN_FRACTION: f32 = 0.02;
+const WIN_FRACTION_TOLERANCE: f32 = 1.5;
+const GENERATED_FOLDER: &str = "generated";
+
+#[derive(Debug, Clone)]
+pub struct GeneratorConfig {
+    pub seed: u64,
+    pub difficulty: f32,
+    pub bombs: (usize, usize),
+    pub obstacles: (usize, usize),
+    pub reversed_chance: f64,
+    pub max_attempts: usize,
+    pub solver: SolverConfig,
+}
+
+impl Default for GeneratorConfig {
+    fn default() -> Self {
+        Self {
+            seed: 0,
+            difficulty: 0.5,
+            bombs: (1, 3),
+            obstacles: (0, 3),
+            reversed_chance: 0.3,
+            max_attempts: 32,
+            solver: SolverConfig {
+                grid_steps: 9,
+                max_grid_samples: 1024,
+                refine_rounds: 2,
+                refine_candidates: 2,
+                max_refine_offsets: 40,
+                max_ticks: 1500,
+            },
+        }
+    }
+}
+
+impl GeneratorConfig {
+    pub fn target_win_fraction(&self) -> f32 {
+        let difficulty = self.difficulty.clamp(0.0, 1.0);
+        (EASY_WIN_FRACTION.ln() + (HARD_WIN_FRACTION.ln() - EASY_WIN_FRACTION.ln()) * difficulty)
+            .exp()
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum GeneratorError {
+    #[error("no solvable level was found in {attempts} attempts")]
+    NoSolution { attempts: usize },
+    #[error("the generated level is invalid: {0}")]
+    InvalidLevel(#[from] LevelLoaderError),
+}
+
+// NOTE: Candidates are built from the seed in turn and rated by the solver, the one closest to the
+// target winning fraction is kept. A candidate is only accepted with at least one verified win.
+pub fn generate_level(
+    config: &GeneratorConfig,
+) -> Result<(LevelConfig, SolverReport), GeneratorError> {
+    let mut rng = StdRng::seed_from_u64(config.seed);
+    let target = config.target_win_fraction();
+    let mut best: Option<(f32, LevelConfig, SolverReport)> = None;
+    for _ in 0..config.max_attempts {
+        let Some(mut level) = build_candidate(&mut rng, config) else {
+            continue;
+        };
+        level.validate()?;
+        let report = solve(&level, &config.solver);
+        if !report.is_solvable() {
+            continue;
+        }
+        let distance = (report.win_fraction().max(f32::EPSILON) / target)
+            .ln()
+            .abs();
+        if best
+            .as_ref()
+            .map_or(true, |(best_distance, ..)| distance < *best_distance)
+        {
+            level.par = report.suggested_par();
+            best = Some((distance, level, report));
+        }
+        if distance <= WIN_FRACTION_TOLERANCE.ln() {
+            break;
+        }
+    }
+    best.map(|(_, level, report)| (level, report))
+        .ok_or(GeneratorError::NoSolution {
+            attempts: config.max_attempts,
+        })
+}
+
+fn build_candidate(rng: &mut StdRng, config: &GeneratorConfig) -> Option<LevelConfig> {
+    let mut placed: Vec<(Vec2, f32)> = vec![];
+    let stone_pos = place_ball(rng, &mut placed, STONE_RADIUS, None)?;
+    let goal_radius = rng.gen_range(20.0..35.0);
+    let goal_pos = place_ball(
+        rng,
+        &mut placed,
+        goal_radius,
+        Some((stone_pos, MIN_GOAL_DISTANCE)),
+    )?;
+    let mut balls = vec![LevelBallConfig {
+        ball_type: BallType::Stone,
+        property: BallProperty::new(Vec2::ZERO, FIELD_RADIUS, BallMovementType::FixedReversed),
+        control_params: BallControlParams::default(),
+        well: None,
+    }];
+    let goal_dir = goal_pos - stone_pos;
+    let goal_angle = goal_dir.y.atan2(goal_dir.x).to_degrees();
+    let angle_center = wrap_angle(goal_angle + rng.gen_range(-90.0..90.0));
+    let angle_half_w: f32 = rng.gen_range(20.0..90.0);
+    let force_min: f32 = rng.gen_range(3.0..6.0);
+    let force_max = force_min + rng.gen_range(4.0..8.0);
+    let mut stone = BallProperty::new(stone_pos, STONE_RADIUS, BallMovementType::Movable);
+    stone.v = Vec2::from_angle(angle_center.to_radians()) * force_min;
+    balls.push(LevelBallConfig {
+        ball_type: BallType::Stone,
+        property: stone,
+        control_params: BallControlParams {
+            force: Some((force_min, force_max)),
+            angle: Some((angle_center - angle_half_w, angle_center + angle_half_w)),
+            ..default()
+        },
+        well: None,
+    });
+    balls.push(fixed_ball(BallType::Goal, goal_pos, goal_radius));
+    for _ in 0..rng.gen_range(config.bombs.0..=config.bombs.1.max(config.bombs.0)) {
+        let radius = rng.gen_range(20.0..45.0);
+        let pos = place_ball(rng, &mut placed, radius, None)?;
+        balls.push(fixed_ball(BallType::Bomb, pos, radius));
+    }
+    // NOTE: Both kinds of obstacles share the count, FixedReversed ones are pits which let the
+    // stone in but not out again
+    for _ in 0..rng.gen_range(config.obstacles.0..=config.obstacles.1.max(config.obstacles.0)) {
+        let radius = rng.gen_range(20.0..60.0);
+        let pos = place_ball(rng, &mut placed, radius, None)?;
+        let movement_type = if rng.gen_bool(config.reversed_chance.clamp(0.0, 1.0)) {
+            BallMovementType::FixedReversed
+        } else {
+            BallMovementType::Fixed
+        };
+        balls.push(LevelBallConfig {
+            ball_type: BallType::Stone,
+            property: BallProperty::new(pos, radius, movement_type),
+            control_params: BallControlParams::default(),
+            well: None,
+        });
+    }
+    Some(LevelConfig {
+        code: format!("practice_{}", config.seed),
+        name: format!("PRACTICE {}", config.seed),
+        balls,
+        ..default()
+    })
+}
+
+fn fixed_ball(ball_type: BallType, pos: Vec2, radius: f32) -> LevelBallConfig {
+    LevelBallConfig {
+        ball_type,
+        property: BallProperty::new(pos, radius, BallMovementType::Fixed),
+        control_params: BallControlParams::default(),
+        well: None,
+    }
+}
+
+// NOTE: Pick a position inside the field without touching the placed balls
+fn place_ball(
+    rng: &mut StdRng,
+    placed: &mut Vec<(Vec2, f32)>,
+    radius: f32,
+    min_distance: Option<(Vec2, f32)>,
+) -> Option<Vec2> {
+    let limit = FIELD_RADIUS - FIELD_MARGIN - radius;
+    for _ in 0..PLACE_TRIES {
+        let pos = Vec2::from_angle(rng.gen_range(0.0..std::f32::consts::TAU))
+            * rng.gen_range(0.0f32..1.0).sqrt()
+            * limit;
+        let is_apart = placed
+            .iter()
+            .all(|(other, other_r)| pos.distance(*other) > radius + other_r + MIN_BALL_GAP);
+        let is_far = min_distance.map_or(true, |(from, distance)| pos.distance(from) >= distance);
+        if is_apart && is_far {
+            placed.push((pos, radius));
+            return Some(pos);
+        }
+    }
+    None
+}
+
+fn wrap_angle(angle: f32) -> f32 {
+    (angle + 180.0).rem_euclid(360.0) - 180.0
+}
+
+// NOTE: Generated levels stay out of the shipped assets unless they are written there on purpose
+pub fn generated_dir_path() -> PathBuf {
+    dirs::config_dir()
+        .map(|native_config_dir| native_config_dir.join(app::APP_CODE))
+        .unwrap_or(Path::new("local").join("configuration"))
+        .join(GENERATED_FOLDER)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn quick_config(seed: u64) -> GeneratorConfig {
+        GeneratorConfig {
+            seed,
+            max_attempts: 4,
+            solver: SolverConfig {
+                grid_steps: 5,
+                max_grid_samples: 64,
+                refine_rounds: 1,
+                refine_candidates: 1,
+                max_refine_offsets: 8,
+                max_ticks: 1500,
+            },
+            ..default()
+        }
+    }
+
+    #[test]
+    fn same_seed_gives_the_same_level() {
+        let mut solved = 0;
+        for seed in 0..4 {
+            let config = quick_config(seed);
+            let first = generate_level(&config).map(|(level, _)| level);
+            let second = generate_level(&config).map(|(level, _)| level);
+            match (first, second) {
+                (Ok(first), Ok(second)) => {
+                    assert!(first.validate().is_ok());
+                    assert_eq!(first.content_hash(), second.content_hash());
+                    solved += 1;
+                }
+                (Err(_), Err(_)) => (),
+                _ => panic!("seed {} does not give the same result", seed),
+            }
+        }
+        assert!(solved > 0);
+    }
+
+    #[test]
+    fn candidates_fit_the_field_without_overlaps() {
+        let config = GeneratorConfig::default();
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut candidates = 0;
+        for _ in 0..64 {
+            let Some(level) = build_candidate(&mut rng, &config) else {
+                continue;
+            };
+            candidates += 1;
+            assert!(level.validate().is_ok());
+            let balls: Vec<&BallProperty> =
+                level.balls[1..].iter().map(|ball| &ball.property).collect();
+            for (index, ball) in balls.iter().enumerate() {
+                assert!(ball.pos.length() + ball.radius <= FIELD_RADIUS - FIELD_MARGIN);
+                for other in balls[index + 1..].iter() {
+                    assert!(ball.pos.distance(other.pos) > ball.radius + other.radius);
+                }
+            }
+        }
+        assert!(candidates > 0);
+    }
+}
@@ -0,0 +1,110 @@
+use super::{
+    level_builder::LevelConfig,
+    simulation::{ControlValues, Simulation},
+    *,
+};
+use crate::app::settings;
+use bevy_prototype_lyon::prelude::*;
+use circular_queue::CircularQueue;
+
+const GHOST_BALL_ALPHA: f32 = 0.2;
+const GHOST_TAILING_ALPHA: f32 = 0.02;
+const GHOST_Z_LAYER: f32 = 0.8;
+
+#[derive(Component)]
+pub struct GhostBall {
+    index: usize,
+    radius: f32,
+    color: Color,
+    tailings: CircularQueue<Vec2>,
+    dyn_entity: Entity,
+}
+
+// NOTE: The ghost replays the personal best with the same simulation as the live attempt
+pub fn build_ghost(
+    level: &LevelConfig,
+    leaderboard: &Leaderboard,
+    settings: &settings::Settings,
+) -> Option<Simulation> {
+    if !settings.is_enabled("ghost") {
+        return None;
+    }
+    let best_history = leaderboard.level_info(level.code.clone()).best_history?;
+    let values = ControlValues::from(&best_history);
+    if !values.fits_level(level) {
+        return None;
+    }
+    Some(Simulation::new(level, &values))
+}
+
+pub fn spawn_ghost_balls(parent: &mut ChildBuilder, ghost: &Simulation) {
+    for (index, ball) in ghost.balls().iter().enumerate() {
+        if ball.property.movement_type != BallMovementType::Movable {
+            continue;
+        }
+        let pos = ball.property.pos;
+        let mut dyn_entity = Entity::PLACEHOLDER;
+        let mut root_entity_commands = parent.spawn(SpatialBundle {
+            transform: Transform::from_xyz(pos.x, pos.y, GHOST_Z_LAYER),
+            ..default()
+        });
+        root_entity_commands.with_children(|parent| {
+            let shape = shapes::Circle {
+                radius: ball.property.radius,
+                center: Vec2::ZERO,
+            };
+            parent.spawn((
+                ShapeBundle {
+                    path: GeometryBuilder::build_as(&shape),
+                    ..default()
+                },
+                Fill::color(ball.ball_type.color().with_alpha(GHOST_BALL_ALPHA)),
+            ));
+            dyn_entity = parent.spawn(SpatialBundle::default()).id();
+        });
+        root_entity_commands.insert(GhostBall {
+            index,
+            radius: ball.property.radius,
+            color: ball.ball_type.color(),
+            tailings: new_tailings(),
+            dyn_entity,
+        });
+    }
+}
+
+pub fn refresh_ghost_balls(
+    mut commands: Commands,
+    mut ghost_query: Query<(&mut GhostBall, &mut Transform)>,
+    game_status: Res<GameStatus>,
+    mut last_time: Local<Option<u32>>,
+) {
+    let Some(ghost) = &game_status.ghost else {
+        *last_time = None;
+        return;
+    };
+    if *last_time == Some(ghost.time()) {
+        return;
+    }
+    *last_time = Some(ghost.time());
+    for (mut ghost_ball, mut trans) in ghost_query.iter_mut() {
+        let Some(sim_ball) = ghost.ball(ghost_ball.index) else {
+            continue;
+        };
+        let pos = sim_ball.property.pos;
+        ghost_ball.tailings.push(pos);
+        trans.translation = pos.extend(GHOST_Z_LAYER);
+        if let Some(mut entity_commands) = commands.get_entity(ghost_ball.dyn_entity) {
+            entity_commands.despawn_descendants();
+            entity_commands.with_children(|parent| {
+                build_tailings(
+                    parent,
+                    &ghost_ball.tailings,
+                    pos,
+                    ghost_ball.radius,
+                    ghost_ball.color.with_alpha(GHOST_TAILING_ALPHA),
+                    0.0,
+                );
+            });
+        }
+    }
+}
@@ -0,0 +1,216 @@
+use super::{
+    simulation::{HitEvent, Simulation},
+    *,
+};
+
+// NOTE: About two minutes of the normal game, longer runs are sampled more sparsely
+const MAX_SAMPLES: usize = 6000;
+
+#[derive(Debug, Clone, Copy)]
+pub struct BallSample {
+    pub pos: Vec2,
+    pub v: Vec2,
+}
+
+#[derive(Debug, Clone)]
+pub struct KinematicsSample {
+    pub tick: u32,
+    pub balls: Vec<BallSample>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum KinematicsQuantity {
+    X,
+    Y,
+    Speed,
+    KineticEnergy,
+    Momentum,
+}
+
+impl KinematicsQuantity {
+    pub const ALL: [KinematicsQuantity; 5] = [
+        KinematicsQuantity::X,
+        KinematicsQuantity::Y,
+        KinematicsQuantity::Speed,
+        KinematicsQuantity::KineticEnergy,
+        KinematicsQuantity::Momentum,
+    ];
+    pub fn label(&self) -> &str {
+        match self {
+            KinematicsQuantity::X => "x(t)",
+            KinematicsQuantity::Y => "y(t)",
+            KinematicsQuantity::Speed => "|v|(t)",
+            KinematicsQuantity::KineticEnergy => "E(t)",
+            KinematicsQuantity::Momentum => "|p|(t) TOTAL",
+        }
+    }
+    // NOTE: The total momentum is a property of the system, the others are per ball
+    pub fn is_total(&self) -> bool {
+        *self == KinematicsQuantity::Momentum
+    }
+}
+
+// NOTE: Samples of the movable balls for every stride ticks of the last run, in simulation units
+#[derive(Resource, Debug, Default)]
+pub struct KinematicsRecord {
+    pub level_code: String,
+    pub balls: Vec<usize>,
+    pub masses: Vec<f32>,
+    pub samples: Vec<KinematicsSample>,
+    pub collisions: Vec<u32>,
+    pub stride: u32,
+    pub last_tick: u32,
+}
+
+impl KinematicsRecord {
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+    pub fn start(&mut self, level_code: &str, simulation: &Simulation) {
+        let movable: Vec<(usize, f32)> = simulation
+            .balls()
+            .iter()
+            .enumerate()
+            .filter(|(_, ball)| ball.property.movement_type == BallMovementType::Movable)
+            .map(|(index, ball)| (index, ball.property.mass()))
+            .collect();
+        self.level_code = String::from(level_code);
+        self.balls = movable.iter().map(|(index, _)| *index).collect();
+        self.masses = movable.iter().map(|(_, mass)| *mass).collect();
+        self.samples.clear();
+        self.collisions.clear();
+        self.stride = 1;
+        self.last_tick = 0;
+    }
+    // NOTE: A tick going backward means a new attempt, so the record starts over
+    pub fn record(&mut self, level_code: &str, simulation: &Simulation, events: &[HitEvent]) {
+        let tick = simulation.time();
+        let is_new_run = self.level_code != level_code
+            || self
+                .samples
+                .last()
+                .map_or(true, |sample| sample.tick >= tick);
+        if is_new_run {
+            self.start(level_code, simulation);
+        }
+        self.last_tick = tick;
+        if events
+            .iter()
+            .any(|event| matches!(event, HitEvent::Bounce { .. } | HitEvent::WallBounce { .. }))
+        {
+            self.collisions.push(tick);
+        }
+        if !self.is_sample_due(tick) {
+            return;
+        }
+        // NOTE: A full record keeps every other sample and doubles the stride, so a long run is
+        // still shown from start to end
+        if self.samples.len() >= MAX_SAMPLES {
+            let mut index = 0;
+            self.samples.retain(|_| {
+                index += 1;
+                index % 2 == 1
+            });
+            self.stride *= 2;
+            if !self.is_sample_due(tick) {
+                return;
+            }
+        }
+        let balls = self
+            .balls
+            .iter()
+            .filter_map(|index| simulation.ball(*index))
+            .map(|ball| BallSample {
+                pos: ball.property.pos,
+                v: ball.property.v,
+            })
+            .collect();
+        self.samples.push(KinematicsSample { tick, balls });
+    }
+    fn is_sample_due(&self, tick: u32) -> bool {
+        match self.samples.last() {
+            Some(sample) => tick >= sample.tick + self.stride,
+            None => true,
+        }
+    }
+    pub fn value(
+        &self,
+        sample: &KinematicsSample,
+        slot: usize,
+        quantity: KinematicsQuantity,
+    ) -> f32 {
+        let Some(ball) = sample.balls.get(slot) else {
+            return 0.0;
+        };
+        match quantity {
+            KinematicsQuantity::X => ball.pos.x,
+            KinematicsQuantity::Y => ball.pos.y,
+            KinematicsQuantity::Speed => ball.v.length(),
+            KinematicsQuantity::KineticEnergy => 0.5 * self.masses[slot] * ball.v.length_squared(),
+            KinematicsQuantity::Momentum => self.total_momentum(sample),
+        }
+    }
+    pub fn total_momentum(&self, sample: &KinematicsSample) -> f32 {
+        sample
+            .balls
+            .iter()
+            .zip(self.masses.iter())
+            .map(|(ball, mass)| ball.v * *mass)
+            .sum::<Vec2>()
+            .length()
+    }
+    // NOTE: The series of a quantity, one per movable ball or a single one for the totals
+    pub fn series(&self, quantity: KinematicsQuantity) -> Vec<Vec<(u32, f32)>> {
+        let slots = if quantity.is_total() {
+            1.min(self.balls.len())
+        } else {
+            self.balls.len()
+        };
+        (0..slots)
+            .map(|slot| {
+                self.samples
+                    .iter()
+                    .map(|sample| (sample.tick, self.value(sample, slot, quantity)))
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{level_builder::LevelConfig, simulation::ControlValues};
+
+    // NOTE: A ball circling in a container never ends the attempt
+    const LEVEL: &str = r#"(
+        name: "TEST",
+        balls: [
+            (ball_type: Stone, property: (pos: (0.0, 0.0), radius: 320.0, movement_type: FixedReversed)),
+            (ball_type: Stone, property: (pos: (-200.0, 0.0), v: (3.0, 2.0), radius: 15.0, movement_type: Movable)),
+        ],
+    )"#;
+
+    #[test]
+    fn long_runs_are_downsampled() {
+        let level = LevelConfig::from_bytes("test", LEVEL.as_bytes()).unwrap();
+        let mut simulation = Simulation::new(&level, &ControlValues::from_level(&level));
+        simulation.start();
+        let mut record = KinematicsRecord::default();
+        let ticks = MAX_SAMPLES as u32 * 3;
+        for _ in 0..ticks {
+            let result = simulation.step();
+            record.record("test", &simulation, &result.events);
+        }
+        assert_eq!(record.last_tick, ticks);
+        assert_eq!(record.stride, 4);
+        assert!(record.samples.len() <= MAX_SAMPLES);
+        assert_eq!(record.samples.first().map(|sample| sample.tick), Some(1));
+        assert!(record.samples.last().unwrap().tick + record.stride > ticks);
+        assert!(record
+            .samples
+            .windows(2)
+            .all(|pair| pair[1].tick - pair[0].tick == record.stride));
+        assert!(!record.collisions.is_empty());
+    }
+}
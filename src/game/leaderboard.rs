@@ -3,4 +3,5 @@ mod plugin;
 
 pub use model::LeaderboardModel as Leaderboard;
 pub use model::LevelHistory;
+pub use model::LevelRecord;
 pub use plugin::LeaderboardPlugin;
@@ -13,6 +13,11 @@ pub struct LevelHistory {
     pub force: Vec<f32>,
     pub angle: Vec<f32>,
     pub created_at: String,
+    // NOTE: Only kept for the current attempt, the stored layout of the histories stays as it was
+    #[serde(skip)]
+    pub collisions: u32,
+    #[serde(skip)]
+    pub stars: u8,
 }
 
 #[derive(Resource, Serialize, Deserialize, Clone, Debug, Default)]
@@ -20,6 +25,8 @@ pub struct LevelRecord {
     pub is_clear: bool,
     pub best_history: Option<LevelHistory>,
     pub histories: Vec<LevelHistory>,
+    #[serde(default)]
+    pub stars: u8,
 }
 
 #[derive(Resource, Serialize, Deserialize)]
@@ -36,6 +43,7 @@ impl LeaderboardModel {
                     is_clear: false,
                     best_history: None,
                     histories: vec![],
+                    stars: 0,
                 },
             );
         }
@@ -47,6 +55,7 @@ impl LeaderboardModel {
                 record.histories.pop();
             }
             if history.is_clear {
+                record.stars = record.stars.max(history.stars);
                 if let Some(best_history) = &record.best_history {
                     if best_history.time > history.time {
                         record.best_history = Some(history);
@@ -68,7 +77,43 @@ impl LeaderboardModel {
                 is_clear: false,
                 best_history: None,
                 histories: vec![],
+                stars: 0,
             }
         }
     }
+    pub fn total_stars(&self) -> u32 {
+        self.level_map
+            .values()
+            .map(|record| record.stars as u32)
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn history(is_clear: bool, stars: u8) -> LevelHistory {
+        LevelHistory {
+            is_clear,
+            stars,
+            ..default()
+        }
+    }
+
+    #[test]
+    fn record_keeps_the_best_stars() {
+        let mut leaderboard = LeaderboardModel {
+            level_map: HashMap::new(),
+        };
+        leaderboard.open_level(String::from("a"));
+        leaderboard.open_level(String::from("b"));
+        leaderboard.store_level_history(String::from("a"), history(true, 2));
+        leaderboard.store_level_history(String::from("a"), history(true, 1));
+        leaderboard.store_level_history(String::from("a"), history(false, 3));
+        leaderboard.store_level_history(String::from("b"), history(true, 3));
+        assert_eq!(leaderboard.level_info(String::from("a")).stars, 2);
+        assert_eq!(leaderboard.level_info(String::from("c")).stars, 0);
+        assert_eq!(leaderboard.total_stars(), 5);
+    }
 }
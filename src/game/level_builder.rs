@@ -1,281 +1,509 @@
-use super::*;
-use lazy_static::lazy_static;
-use std::collections::HashMap;
+use super::{
+    force_field::ForceZone, par::ParConfig, trajectory::PreviewConfig, wall::WallShape, *,
+};
+use bevy::asset::{
+    io::Reader, AssetLoader, AsyncReadExt, LoadContext, LoadedFolder, RecursiveDependencyLoadState,
+};
+use serde::{Deserialize, Serialize};
+use std::{fs, path::Path};
+use thiserror::Error;
 
-#[derive(Debug)]
+pub const LEVEL_FOLDER: &str = "levels";
+pub const LEVEL_EXT: &str = "level.ron";
+pub const CATALOG_EXT: &str = "catalog.ron";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LevelBallConfig {
     pub ball_type: BallType,
     pub property: BallProperty,
+    #[serde(default)]
     pub control_params: BallControlParams,
+    #[serde(default)]
+    pub well: Option<WellParams>,
 }
 
-#[derive(Default, Debug)]
+#[derive(Asset, TypePath, Default, Debug, Clone, Serialize, Deserialize)]
 pub struct LevelConfig {
-    pub name: &'static str,
+    #[serde(skip)]
+    pub code: String,
+    pub name: String,
     pub balls: Vec<LevelBallConfig>,
+    #[serde(default)]
+    pub walls: Vec<WallShape>,
+    #[serde(default)]
+    pub gravity: Vec2,
+    #[serde(default)]
+    pub zones: Vec<ForceZone>,
+    #[serde(default)]
+    pub preview: PreviewConfig,
+    #[serde(default)]
+    pub par: Option<ParConfig>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub enum UnlockRule {
+    Open,
+    #[default]
+    ClearPrevious,
+    ClearAhead(usize),
+}
+
+// NOTE: A pack starts at its first level and lasts until the next pack
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LevelPack {
+    pub name: String,
+    pub start: String,
+    #[serde(default)]
+    pub required_stars: u32,
+}
+
+#[derive(Asset, TypePath, Default, Debug, Clone, Serialize, Deserialize)]
+pub struct LevelCatalog {
+    pub levels: Vec<String>,
+    #[serde(default)]
+    pub unlock: UnlockRule,
+    #[serde(default)]
+    pub packs: Vec<LevelPack>,
+}
+
+impl LevelCatalog {
+    pub fn pack_of(&self, index: usize) -> Option<&LevelPack> {
+        self.packs
+            .iter()
+            .filter(|pack| {
+                self.levels
+                    .iter()
+                    .position(|code| *code == pack.start)
+                    .map_or(false, |start| start <= index)
+            })
+            .max_by_key(|pack| self.levels.iter().position(|code| *code == pack.start))
+    }
+    pub fn required_stars(&self, index: usize) -> u32 {
+        self.pack_of(index).map_or(0, |pack| pack.required_stars)
+    }
+    // NOTE: The first level is always playable, the others follow the unlock rule in order
+    pub fn is_unlocked(
+        &self,
+        index: usize,
+        total_stars: u32,
+        is_clear: impl Fn(&str) -> bool,
+    ) -> bool {
+        if total_stars < self.required_stars(index) {
+            return false;
+        }
+        if index == 0 {
+            return true;
+        }
+        match self.unlock {
+            UnlockRule::Open => true,
+            UnlockRule::ClearPrevious => self
+                .levels
+                .get(index - 1)
+                .map_or(false, |code| is_clear(code)),
+            UnlockRule::ClearAhead(count) => {
+                let uncleared = self.levels[..index.min(self.levels.len())]
+                    .iter()
+                    .filter(|code| !is_clear(code))
+                    .count();
+                uncleared <= count
+            }
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum LevelLoaderError {
+    #[error("could not read the level file: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("could not parse the level file: {0}")]
+    Ron(#[from] ron::error::SpannedError),
+    #[error("the level has no balls")]
+    NoBalls,
+    #[error("ball #{index} has an invalid radius {radius}, it should be positive")]
+    InvalidRadius { index: usize, radius: f32 },
+    #[error("ball #{index} has an invalid {field} range ({min}, {max})")]
+    InvalidControlRange {
+        index: usize,
+        field: &'static str,
+        min: f32,
+        max: f32,
+    },
+    #[error("ball #{index} has an invalid {field} {value}")]
+    InvalidProperty {
+        index: usize,
+        field: &'static str,
+        value: f32,
+    },
+    #[error("ball #{index} has well params but it is not an attractor")]
+    UnexpectedWell { index: usize },
+    #[error("wall #{index} is invalid, {reason}")]
+    InvalidWall { index: usize, reason: &'static str },
+    #[error("zone #{index} is invalid, {reason}")]
+    InvalidZone { index: usize, reason: &'static str },
+    #[error("the par is invalid, {0}")]
+    InvalidPar(&'static str),
+}
+
+#[derive(Debug, Error)]
+pub enum LevelSaverError {
+    #[error("could not write the level file: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("could not serialize the level: {0}")]
+    Serialize(#[from] ron::Error),
 }
 
-const BL_LIST_GAP: f32 = 150.0;
-const BL_BALL_SIZE: f32 = 30.0;
-
-pub const LEVELS: [&str; 7] = ["hello", "timing", "twice", "maze", "star", "chaser", "pool"];
-lazy_static! {
-    pub static ref LEVEL_MAP: HashMap<&'static str, LevelConfig> = {
-        let mut m = HashMap::new();
-        m.insert(
-            "ball_list",
-            LevelConfig {
-                name: "BALL LIST",
-                balls: vec![
-                    LevelBallConfig {
-                        ball_type: BallType::Stone,
-                        property: BallProperty {
-                            pos: Vec2::new(-BL_LIST_GAP, BL_LIST_GAP),
-                            v: Vec2::new(0.0, 0.0),
-                            radius: BL_BALL_SIZE,
-                            movement_type: BallMovementType::Movable,
-                        },
-                        control_params: BallControlParams::default(),
-                    },
-                    LevelBallConfig {
-                        ball_type: BallType::Stone,
-                        property: BallProperty {
-                            pos: Vec2::new(-BL_LIST_GAP, 0.0),
-                            v: Vec2::new(0.0, 0.0),
-                            radius: BL_BALL_SIZE,
-                            movement_type: BallMovementType::Fixed,
-                        },
-                        control_params: BallControlParams::default(),
-                    },
-                    LevelBallConfig {
-                        ball_type: BallType::Stone,
-                        property: BallProperty {
-                            pos: Vec2::new(-BL_LIST_GAP, -BL_LIST_GAP),
-                            v: Vec2::new(0.0, 0.0),
-                            radius: BL_BALL_SIZE,
-                            movement_type: BallMovementType::FixedReversed,
-                        },
-                        control_params: BallControlParams::default(),
-                    },
-                    LevelBallConfig {
-                        ball_type: BallType::Goal,
-                        property: BallProperty {
-                            pos: Vec2::new(0.0, BL_LIST_GAP),
-                            v: Vec2::new(0.0, 0.0),
-                            radius: BL_BALL_SIZE,
-                            movement_type: BallMovementType::Movable,
-                        },
-                        control_params: BallControlParams::default(),
-                    },
-                    LevelBallConfig {
-                        ball_type: BallType::Goal,
-                        property: BallProperty {
-                            pos: Vec2::new(0.0, 0.0),
-                            v: Vec2::new(0.0, 0.0),
-                            radius: BL_BALL_SIZE,
-                            movement_type: BallMovementType::Fixed,
-                        },
-                        control_params: BallControlParams::default(),
-                    },
-                    LevelBallConfig {
-                        ball_type: BallType::Goal,
-                        property: BallProperty {
-                            pos: Vec2::new(0.0, -BL_LIST_GAP),
-                            v: Vec2::new(0.0, 0.0),
-                            radius: BL_BALL_SIZE,
-                            movement_type: BallMovementType::FixedReversed,
-                        },
-                        control_params: BallControlParams::default(),
-                    },
-                    LevelBallConfig {
-                        ball_type: BallType::Bomb,
-                        property: BallProperty {
-                            pos: Vec2::new(BL_LIST_GAP, BL_LIST_GAP),
-                            v: Vec2::new(0.0, 0.0),
-                            radius: BL_BALL_SIZE,
-                            movement_type: BallMovementType::Movable,
-                        },
-                        control_params: BallControlParams::default(),
-                    },
-                    LevelBallConfig {
-                        ball_type: BallType::Bomb,
-                        property: BallProperty {
-                            pos: Vec2::new(BL_LIST_GAP, 0.0),
-                            v: Vec2::new(0.0, 0.0),
-                            radius: BL_BALL_SIZE,
-                            movement_type: BallMovementType::Fixed,
-                        },
-                        control_params: BallControlParams::default(),
-                    },
-                    LevelBallConfig {
-                        ball_type: BallType::Bomb,
-                        property: BallProperty {
-                            pos: Vec2::new(BL_LIST_GAP, -BL_LIST_GAP),
-                            v: Vec2::new(0.0, 0.0),
-                            radius: BL_BALL_SIZE,
-                            movement_type: BallMovementType::FixedReversed,
-                        },
-                        control_params: BallControlParams::default(),
-                    },
-                ],
-            },
+impl LevelConfig {
+    pub fn from_bytes(code: &str, bytes: &[u8]) -> Result<Self, LevelLoaderError> {
+        let mut level: LevelConfig = ron::de::from_bytes(bytes)?;
+        level.code = String::from(code);
+        level.validate()?;
+        Ok(level)
+    }
+    pub fn save(&self, path: &Path) -> Result<(), LevelSaverError> {
+        let content = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())?;
+        fs::write(path, content)?;
+        Ok(())
+    }
+    pub fn validate(&self) -> Result<(), LevelLoaderError> {
+        if self.balls.is_empty() {
+            return Err(LevelLoaderError::NoBalls);
+        }
+        for (index, ball) in self.balls.iter().enumerate() {
+            let radius = ball.property.radius;
+            if radius.is_nan() || radius <= 0.0 {
+                return Err(LevelLoaderError::InvalidRadius { index, radius });
+            }
+            let property = &ball.property;
+            let unit_values = [
+                ("restitution", property.restitution),
+                ("friction", property.friction),
+                ("damping", property.damping),
+            ];
+            for (field, value) in unit_values {
+                if !(0.0..=1.0).contains(&value) {
+                    return Err(LevelLoaderError::InvalidProperty {
+                        index,
+                        field,
+                        value,
+                    });
+                }
+            }
+            if let Some(well) = &ball.well {
+                if ball.ball_type != BallType::Attractor {
+                    return Err(LevelLoaderError::UnexpectedWell { index });
+                }
+                let well_values = [("well range", well.range), ("well falloff", well.falloff)];
+                for (field, value) in well_values {
+                    if value.is_nan() || value < 0.0 {
+                        return Err(LevelLoaderError::InvalidProperty {
+                            index,
+                            field,
+                            value,
+                        });
+                    }
+                }
+            }
+            let positive_values = [
+                ("mass", property.mass.unwrap_or(1.0)),
+                ("density", property.density),
+            ];
+            for (field, value) in positive_values {
+                if value.is_nan() || value <= 0.0 {
+                    return Err(LevelLoaderError::InvalidProperty {
+                        index,
+                        field,
+                        value,
+                    });
+                }
+            }
+            let ranges = [
+                ("x", ball.control_params.x),
+                ("y", ball.control_params.y),
+                ("force", ball.control_params.force),
+                ("angle", ball.control_params.angle),
+            ];
+            for (field, range) in ranges {
+                if let Some((min, max)) = range {
+                    if min.is_nan() || max.is_nan() || min > max {
+                        return Err(LevelLoaderError::InvalidControlRange {
+                            index,
+                            field,
+                            min,
+                            max,
+                        });
+                    }
+                }
+            }
+        }
+        for (index, wall) in self.walls.iter().enumerate() {
+            wall.validate()
+                .map_err(|reason| LevelLoaderError::InvalidWall { index, reason })?;
+        }
+        for (index, zone) in self.zones.iter().enumerate() {
+            zone.validate()
+                .map_err(|reason| LevelLoaderError::InvalidZone { index, reason })?;
+        }
+        if let Some(par) = &self.par {
+            par.validate().map_err(LevelLoaderError::InvalidPar)?;
+        }
+        Ok(())
+    }
+    // NOTE: FNV-1a of the parsed config, so formatting and comments in the file do not matter
+    pub fn content_hash(&self) -> u64 {
+        let content = ron::to_string(self).unwrap_or_default();
+        content.bytes().fold(0xcbf29ce484222325, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+        })
+    }
+}
+
+#[derive(Default)]
+pub struct LevelConfigLoader;
+
+impl AssetLoader for LevelConfigLoader {
+    type Asset = LevelConfig;
+    type Settings = ();
+    type Error = LevelLoaderError;
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        _settings: &'a (),
+        load_context: &'a mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        let code = load_context
+            .path()
+            .file_name()
+            .and_then(|name| name.to_str())
+            .and_then(|name| name.split('.').next())
+            .unwrap_or_default();
+        LevelConfig::from_bytes(code, &bytes)
+    }
+    fn extensions(&self) -> &[&str] {
+        &[LEVEL_EXT]
+    }
+}
+
+#[derive(Default)]
+pub struct LevelCatalogLoader;
+
+impl AssetLoader for LevelCatalogLoader {
+    type Asset = LevelCatalog;
+    type Settings = ();
+    type Error = LevelLoaderError;
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        Ok(ron::de::from_bytes(&bytes)?)
+    }
+    fn extensions(&self) -> &[&str] {
+        &[CATALOG_EXT]
+    }
+}
+
+// NOTE: The handle keeps the level assets loaded for the whole run
+#[derive(Resource)]
+pub struct LevelFolder(Handle<LoadedFolder>);
+
+impl LevelFolder {
+    // NOTE: A level file which fails to load does not hold back the others
+    pub fn is_settled(&self, asset_server: &AssetServer) -> bool {
+        matches!(
+            asset_server.get_recursive_dependency_load_state(&self.0),
+            Some(RecursiveDependencyLoadState::Loaded | RecursiveDependencyLoadState::Failed)
+        )
+    }
+}
+
+pub fn load_levels(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(LevelFolder(asset_server.load_folder(LEVEL_FOLDER)));
+}
+
+pub fn fetch_catalog(catalog_assets: &Assets<LevelCatalog>) -> Option<&LevelCatalog> {
+    catalog_assets.iter().next().map(|(_, catalog)| catalog)
+}
+
+pub fn level_codes(catalog_assets: &Assets<LevelCatalog>) -> Vec<String> {
+    fetch_catalog(catalog_assets)
+        .map(|catalog| catalog.levels.clone())
+        .unwrap_or_default()
+}
+
+pub fn fetch_level<'a>(
+    level_assets: &'a Assets<LevelConfig>,
+    code: &str,
+) -> Option<(AssetId<LevelConfig>, &'a LevelConfig)> {
+    level_assets.iter().find(|(_, level)| level.code == code)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::replay::{ReplayFile, ReplayFileError};
+
+    const LEVEL: &str = r#"(
+        name: "TEST",
+        balls: [
+            (ball_type: Stone, property: (pos: (0.0, 0.0), radius: 320.0, movement_type: FixedReversed)),
+            (
+                ball_type: Stone,
+                property: (pos: (-200.0, 0.0), v: (8.0, 0.0), radius: 15.0, movement_type: Movable),
+                control_params: (force: Some((4.0, 12.0)), angle: Some((-45.0, 45.0))),
+            ),
+            (ball_type: Goal, property: (pos: (200.0, 0.0), radius: 30.0, movement_type: Fixed)),
+        ],
+    )"#;
+
+    fn level() -> LevelConfig {
+        LevelConfig::from_bytes("test", LEVEL.as_bytes()).unwrap()
+    }
+
+    #[test]
+    fn load_sets_code_from_file() {
+        let level = level();
+        assert_eq!(level.code, "test");
+        assert_eq!(level.balls.len(), 3);
+    }
+
+    #[test]
+    fn reject_invalid_levels() {
+        let mut no_balls = level();
+        no_balls.balls.clear();
+        assert!(matches!(
+            no_balls.validate(),
+            Err(LevelLoaderError::NoBalls)
+        ));
+
+        let mut zero_radius = level();
+        zero_radius.balls[2].property.radius = 0.0;
+        assert!(matches!(
+            zero_radius.validate(),
+            Err(LevelLoaderError::InvalidRadius { index: 2, .. })
+        ));
+
+        let mut bad_restitution = level();
+        bad_restitution.balls[1].property.restitution = 1.5;
+        assert!(matches!(
+            bad_restitution.validate(),
+            Err(LevelLoaderError::InvalidProperty {
+                index: 1,
+                field: "restitution",
+                ..
+            })
+        ));
+
+        let mut bad_mass = level();
+        bad_mass.balls[1].property.mass = Some(0.0);
+        assert!(matches!(
+            bad_mass.validate(),
+            Err(LevelLoaderError::InvalidProperty {
+                index: 1,
+                field: "mass",
+                ..
+            })
+        ));
+
+        let mut misplaced_well = level();
+        misplaced_well.balls[2].well = Some(WellParams::default());
+        assert!(matches!(
+            misplaced_well.validate(),
+            Err(LevelLoaderError::UnexpectedWell { index: 2 })
+        ));
+
+        let mut reversed_range = level();
+        reversed_range.balls[1].control_params.force = Some((12.0, 4.0));
+        assert!(matches!(
+            reversed_range.validate(),
+            Err(LevelLoaderError::InvalidControlRange {
+                index: 1,
+                field: "force",
+                ..
+            })
+        ));
+
+        let mut empty_wall = level();
+        empty_wall.walls.push(WallShape::Segment {
+            start: Vec2::ONE,
+            end: Vec2::ONE,
+        });
+        assert!(matches!(
+            empty_wall.validate(),
+            Err(LevelLoaderError::InvalidWall { index: 0, .. })
+        ));
+    }
+
+    fn catalog(packs: &str) -> LevelCatalog {
+        let catalog = format!(r#"(levels: ["a", "b", "c", "d"], packs: [{packs}])"#);
+        ron::de::from_bytes(catalog.as_bytes()).unwrap()
+    }
+
+    #[test]
+    fn packs_require_stars_from_their_start() {
+        let catalog = catalog(
+            r#"(name: "BASICS", start: "a"), (name: "ADVANCED", start: "c", required_stars: 6)"#,
         );
-        m.insert(
-            "simple",
-            LevelConfig {
-                name: "SIMPLE",
-                balls: vec![
-                    LevelBallConfig {
-                        ball_type: BallType::Stone,
-                        property: BallProperty {
-                            pos: Vec2::new(-300.0, 0.0),
-                            v: Vec2::new(0.0, 0.0),
-                            radius: 200.0,
-                            movement_type: BallMovementType::FixedReversed,
-                        },
-                        control_params: BallControlParams::default(),
-                    },
-                    LevelBallConfig {
-                        ball_type: BallType::Stone,
-                        property: BallProperty {
-                            pos: Vec2::new(-300.0, 10.0),
-                            v: Vec2::new(10.0, 0.0),
-                            radius: 10.0,
-                            movement_type: BallMovementType::Movable,
-                        },
-                        control_params: BallControlParams::default(),
-                    },
-                    LevelBallConfig {
-                        ball_type: BallType::Stone,
-                        property: BallProperty {
-                            pos: Vec2::new(-300.0, 40.0),
-                            v: Vec2::new(5.0, 0.0),
-                            radius: 10.0,
-                            movement_type: BallMovementType::Movable,
-                        },
-                        control_params: BallControlParams::default(),
-                    },
-                    LevelBallConfig {
-                        ball_type: BallType::Stone,
-                        property: BallProperty {
-                            pos: Vec2::new(-300.0, 70.0),
-                            v: Vec2::new(5.0, 0.0),
-                            radius: 15.0,
-                            movement_type: BallMovementType::Movable,
-                        },
-                        control_params: BallControlParams::default(),
-                    },
-                    LevelBallConfig {
-                        ball_type: BallType::Stone,
-                        property: BallProperty {
-                            pos: Vec2::new(-350.0, 70.0),
-                            v: Vec2::new(0.0, 0.0),
-                            radius: 20.0,
-                            movement_type: BallMovementType::Fixed,
-                        },
-                        control_params: BallControlParams::default(),
-                    },
-                ],
-            },
+        let required: Vec<u32> = (0..4).map(|index| catalog.required_stars(index)).collect();
+        assert_eq!(required, vec![0, 0, 6, 6]);
+        let all_clear = |_: &str| true;
+        assert!(!catalog.is_unlocked(2, 5, all_clear));
+        assert!(catalog.is_unlocked(2, 6, all_clear));
+        assert!(!catalog.is_unlocked(3, 5, all_clear));
+        assert!(catalog.is_unlocked(1, 0, all_clear));
+        // NOTE: Enough stars do not skip the unlock rule
+        assert!(!catalog.is_unlocked(2, 6, |code| code == "a"));
+    }
+
+    #[test]
+    fn packs_with_unknown_start_are_ignored() {
+        let catalog = catalog(
+            r#"(name: "ADVANCED", start: "c", required_stars: 6), (name: "LOST", start: "x", required_stars: 100)"#,
         );
-        m.insert(
-            "hit_test",
-            LevelConfig {
-                name: "SIMPLE",
-                balls: vec![
-                    LevelBallConfig {
-                        ball_type: BallType::Stone,
-                        property: BallProperty {
-                            pos: Vec2::new(0.0, 0.0),
-                            v: Vec2::new(0.0, 0.0),
-                            radius: 200.0,
-                            movement_type: BallMovementType::FixedReversed,
-                        },
-                        control_params: BallControlParams::default(),
-                    },
-                    LevelBallConfig {
-                        ball_type: BallType::Bomb,
-                        property: BallProperty {
-                            pos: Vec2::new(-10.0, 190.0),
-                            v: Vec2::new(-10.0, -10.0),
-                            radius: 10.0,
-                            movement_type: BallMovementType::Movable,
-                        },
-                        control_params: BallControlParams::default(),
-                    },
-                    LevelBallConfig {
-                        ball_type: BallType::Bomb,
-                        property: BallProperty {
-                            pos: Vec2::new(0.0, 170.0),
-                            v: Vec2::new(12.0, 0.0),
-                            radius: 10.0,
-                            movement_type: BallMovementType::Movable,
-                        },
-                        control_params: BallControlParams::default(),
-                    },
-                    LevelBallConfig {
-                        ball_type: BallType::Bomb,
-                        property: BallProperty {
-                            pos: Vec2::new(0.0, 150.0),
-                            v: Vec2::new(-14.0, 0.0),
-                            radius: 10.0,
-                            movement_type: BallMovementType::Movable,
-                        },
-                        control_params: BallControlParams::default(),
-                    },
-                    LevelBallConfig {
-                        ball_type: BallType::Bomb,
-                        property: BallProperty {
-                            pos: Vec2::new(0.0, 0.0),
-                            v: Vec2::new(0.0, 0.0),
-                            radius: 20.0,
-                            movement_type: BallMovementType::Movable,
-                        },
-                        control_params: BallControlParams::default(),
-                    },
-                    LevelBallConfig {
-                        ball_type: BallType::Goal,
-                        property: BallProperty {
-                            pos: Vec2::new(-60.0, 0.0),
-                            v: Vec2::new(1.0, 0.0),
-                            radius: 30.0,
-                            movement_type: BallMovementType::Movable,
-                        },
-                        control_params: BallControlParams::default(),
-                    },
-                    LevelBallConfig {
-                        ball_type: BallType::Bomb,
-                        property: BallProperty {
-                            pos: Vec2::new(-60.0, -80.0),
-                            v: Vec2::new(0.0, 0.0),
-                            radius: 50.0,
-                            movement_type: BallMovementType::Fixed,
-                        },
-                        control_params: BallControlParams::default(),
-                    },
-                    // LevelBallConfig {
-                    //     ball_type: BallType::Stone,
-                    //     property: BallProperty {
-                    //         pos: Vec2::new(100.0, 300.0),
-                    //         v: Vec2::new(-50.0, 0.0),
-                    //         radius: 10.0,
-                    //         movement_type: BallMovementType::Movable,
-                    //     },
-                    //     control_params: BallControlParams::default(),
-                    // },
-                    // LevelBallConfig {
-                    //     ball_type: BallType::Stone,
-                    //     property: BallProperty {
-                    //         pos: Vec2::new(-100.0, 300.0),
-                    //         v: Vec2::new(10.0, 0.0),
-                    //         radius: 50.0,
-                    //         movement_type: BallMovementType::Movable,
-                    //     },
-                    //     control_params: BallControlParams::default(),
-                    // },
-                ],
-            },
+        assert_eq!(catalog.required_stars(0), 0);
+        assert_eq!(catalog.required_stars(3), 6);
+        assert!(catalog.is_unlocked(0, 0, |_| false));
+        assert!(catalog.is_unlocked(3, 6, |_| true));
+        assert_eq!(
+            catalog.pack_of(3).map(|pack| pack.name.as_str()),
+            Some("ADVANCED")
         );
-        m
-    };
+    }
+
+    #[test]
+    fn refuse_replays_of_changed_levels() {
+        assert_eq!(level().content_hash(), level().content_hash());
+        let level = level();
+        let replay_file = ReplayFile::new(&level, &LevelHistory::default());
+        let mut level_assets = Assets::<LevelConfig>::default();
+        let handle = level_assets.add(level);
+        assert!(replay_file.check_level(&level_assets).is_ok());
+
+        level_assets.get_mut(&handle).unwrap().balls[2]
+            .property
+            .pos
+            .x = 210.0;
+        assert!(matches!(
+            replay_file.check_level(&level_assets),
+            Err(ReplayFileError::LevelMismatch(_))
+        ));
+
+        level_assets.get_mut(&handle).unwrap().code = String::from("other");
+        assert!(matches!(
+            replay_file.check_level(&level_assets),
+            Err(ReplayFileError::UnknownLevel(_))
+        ));
+    }
+
+    #[test]
+    fn reject_unparsable_files() {
+        assert!(matches!(
+            LevelConfig::from_bytes("test", b"(name: \"TEST\""),
+            Err(LevelLoaderError::Ron(_))
+        ));
+    }
 }
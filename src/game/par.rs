@@ -0,0 +1,111 @@
+use super::LevelHistory;
+use serde::{Deserialize, Serialize};
+
+pub const MAX_STARS: u8 = 3;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ParConfig {
+    pub time: u32,
+    #[serde(default)]
+    pub force: Option<f32>,
+    #[serde(default)]
+    pub collisions: Option<u32>,
+}
+
+impl ParConfig {
+    pub fn validate(&self) -> Result<(), &'static str> {
+        if self.time == 0 {
+            return Err("the par time should be positive");
+        }
+        if let Some(force) = self.force {
+            if force.is_nan() || force <= 0.0 {
+                return Err("the par force should be positive");
+            }
+        }
+        Ok(())
+    }
+    // NOTE: 1 star for a clear, 2 for a clear within the par time and 3 if the optional targets
+    // are met as well. Levels without optional targets give 3 stars within the par time.
+    pub fn rate(&self, history: &LevelHistory) -> u8 {
+        if !history.is_clear {
+            return 0;
+        }
+        if history.time > self.time {
+            return 1;
+        }
+        let total_force: f32 = history.force.iter().sum();
+        let is_force_met = self.force.map_or(true, |force| total_force <= force);
+        let is_collisions_met = self
+            .collisions
+            .map_or(true, |collisions| history.collisions <= collisions);
+        if is_force_met && is_collisions_met {
+            MAX_STARS
+        } else {
+            2
+        }
+    }
+}
+
+pub fn rate_history(par: Option<&ParConfig>, history: &LevelHistory) -> u8 {
+    match par {
+        Some(par) => par.rate(history),
+        None => u8::from(history.is_clear),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn history(is_clear: bool, time: u32, force: f32, collisions: u32) -> LevelHistory {
+        LevelHistory {
+            is_clear,
+            time,
+            force: vec![force],
+            collisions,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn time_alone_gives_one_to_three_stars() {
+        let par = ParConfig {
+            time: 100,
+            ..Default::default()
+        };
+        assert_eq!(par.rate(&history(false, 50, 5.0, 0)), 0);
+        assert_eq!(par.rate(&history(true, 101, 5.0, 0)), 1);
+        assert_eq!(par.rate(&history(true, 100, 5.0, 0)), MAX_STARS);
+        assert_eq!(par.rate(&history(true, 1, 500.0, 500)), MAX_STARS);
+    }
+
+    #[test]
+    fn optional_targets_hold_back_the_third_star() {
+        let par = ParConfig {
+            time: 100,
+            force: Some(8.0),
+            collisions: Some(2),
+        };
+        assert_eq!(par.rate(&history(true, 101, 8.0, 2)), 1);
+        assert_eq!(par.rate(&history(true, 100, 8.0, 2)), MAX_STARS);
+        assert_eq!(par.rate(&history(true, 100, 8.1, 2)), 2);
+        assert_eq!(par.rate(&history(true, 100, 8.0, 3)), 2);
+        let par = ParConfig { force: None, ..par };
+        assert_eq!(par.rate(&history(true, 100, 800.0, 2)), MAX_STARS);
+        assert_eq!(par.rate(&history(true, 100, 800.0, 3)), 2);
+    }
+
+    #[test]
+    fn levels_without_par_give_one_star_for_a_clear() {
+        assert_eq!(rate_history(None, &history(true, 10, 5.0, 0)), 1);
+        assert_eq!(rate_history(None, &history(false, 10, 5.0, 0)), 0);
+        let par = ParConfig {
+            time: 5,
+            ..Default::default()
+        };
+        assert_eq!(
+            rate_history(Some(&par), &history(true, 5, 5.0, 0)),
+            MAX_STARS
+        );
+    }
+}
@@ -23,6 +23,11 @@ pub trait PhaseBase {
     fn build(&self, app: &mut App);
 }
 
+// NOTE: Pausing keeps the level as it is, its effects are only cleared when the level is left
+fn is_leaving_level(game_status: Res<GameStatus>) -> bool {
+    game_status.resume_mode.is_none()
+}
+
 pub const PHASES: [&dyn PhaseBase; 6] = [
     &setup::Phase,
     &ready::Phase,
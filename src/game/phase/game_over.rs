@@ -1,5 +1,5 @@
 use super::*;
-use crate::app::anime_effect;
+use crate::app::{anime_effect, key_binding};
 
 pub struct Phase;
 
@@ -9,6 +9,7 @@ impl PhaseBase for Phase {
     }
     fn build(&self, app: &mut App) {
         app.add_systems(OnEnter(self.state()), state_enter)
+            .add_systems(Update, handle_replay_restart.run_if(in_state(self.state())))
             .add_systems(
                 OnExit(self.state()),
                 (anime_effect::clear_anime_effect, state_exit),
@@ -16,6 +17,21 @@ impl PhaseBase for Phase {
     }
 }
 
-fn state_enter() {}
+// NOTE: The results panel is navigated with the menu keys
+fn state_enter(mut key_binding: ResMut<key_binding::KeyBindingConfig>) {
+    key_binding.mode = key_binding::KeyBindingMode::Navgation;
+}
+
+fn state_exit(mut key_binding: ResMut<key_binding::KeyBindingConfig>) {
+    key_binding.mode = key_binding::KeyBindingMode::Gaming;
+}
 
-fn state_exit() {}
+fn handle_replay_restart(
+    key_input: Res<ButtonInput<KeyCode>>,
+    game_status: Res<GameStatus>,
+    mut game_phase: ResMut<NextState<PhaseState>>,
+) {
+    if game_status.replay.is_some() && key_input.just_pressed(KeyCode::KeyR) {
+        game_phase.set(PhaseState::Preparing);
+    }
+}
@@ -1,5 +1,10 @@
 use super::*;
-use crate::app::anime_effect;
+use crate::{
+    app::{anime_effect, audio, element, interaction, key_binding, layer, theme, ui},
+    book::PageState,
+};
+use bevy_alt_ui_navigation_lite::{prelude::*, NavRequestSystem};
+use bevy_prototype_lyon::prelude::*;
 
 pub struct Phase;
 
@@ -8,14 +13,225 @@ impl PhaseBase for Phase {
         PhaseState::Paused
     }
     fn build(&self, app: &mut App) {
-        app.add_systems(OnEnter(self.state()), state_enter)
-            .add_systems(
-                OnExit(self.state()),
-                (anime_effect::clear_anime_effect, state_exit),
-            );
+        app.add_systems(
+            OnEnter(self.state()),
+            (
+                interaction::reset_default_focus,
+                state_enter,
+                audio::reduce_bgm_volume,
+            ),
+        )
+        .add_systems(
+            Update,
+            (
+                handle_resume_key,
+                (
+                    handle_pause_navigation,
+                    interaction::handle_default_focus,
+                    element::element_systems(),
+                )
+                    .after(NavRequestSystem),
+            )
+                .run_if(in_state(self.state()).and_then(not(is_in_pause_settings))),
+        )
+        .add_systems(
+            OnExit(self.state()),
+            (
+                anime_effect::clear_anime_effect.run_if(is_leaving_level),
+                interaction::clear_focus_effect.run_if(not(is_leaving_level)),
+                element::clear_elements,
+                ui::despawn_ui::<PauseOverlay>,
+                ui::despawn_ui::<PauseCover>,
+                audio::reset_bgm_volume,
+                state_exit,
+            ),
+        )
+        // NOTE: The overlay is taken down for the settings and built again when they are closed
+        .add_systems(
+            OnExit(PageState::Game),
+            (
+                interaction::clear_focus_effect,
+                element::clear_elements,
+                ui::despawn_ui::<PauseOverlay>,
+                ui::despawn_ui::<PauseCover>,
+            )
+                .run_if(in_state(self.state())),
+        )
+        .add_systems(
+            OnEnter(PageState::Game),
+            (interaction::reset_default_focus, state_enter).run_if(in_state(self.state())),
+        );
     }
 }
 
-fn state_enter() {}
+#[derive(Component)]
+struct PauseOverlay;
 
-fn state_exit() {}
+#[derive(Component)]
+struct PauseCover;
+
+#[derive(Component)]
+enum PauseAction {
+    Resume,
+    Restart,
+    Settings,
+    MoveToPage(PageState),
+}
+
+const PAUSE_TITLE_FS: f32 = ui::FONT_SIZE * 1.5;
+const PAUSE_COVER_ALPHA: f32 = 0.8;
+
+fn state_enter(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    window_query: Query<&Window>,
+    cover_query: Query<Entity, With<GameCover>>,
+    mut key_binding: ResMut<key_binding::KeyBindingConfig>,
+) {
+    key_binding.mode = key_binding::KeyBindingMode::Navgation;
+    let window = window_query.single();
+    let cover_entity = cover_query.get_single().unwrap();
+    commands.entity(cover_entity).with_children(|parent| {
+        let rect = shapes::Rectangle {
+            extents: Vec2::new(window.width(), window.height()),
+            ..default()
+        };
+        parent.spawn((
+            ShapeBundle {
+                path: GeometryBuilder::build_as(&rect),
+                ..default()
+            },
+            Fill::color(theme::DARK_BG_COLOR.with_alpha(PAUSE_COVER_ALPHA)),
+            PauseCover,
+        ));
+    });
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    row_gap: ui::px_p(4.0),
+                    ..default()
+                },
+                z_index: ZIndex::Global(layer::PAGE_UI_Z_INDEX + 1),
+                ..default()
+            },
+            PauseOverlay,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                "PAUSED",
+                TextStyle {
+                    font: asset_server.load(theme::FONT),
+                    font_size: PAUSE_TITLE_FS,
+                    color: theme::FG_COLOR,
+                },
+            ));
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        display: Display::Grid,
+                        grid_template_columns: vec![GridTrack::fr(1.0), GridTrack::fr(1.0)],
+                        column_gap: ui::px_p(ui::MENU_ENTRY_PADDING),
+                        row_gap: ui::px_p(ui::MENU_ENTRY_PADDING),
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|parent| {
+                    element::build_element(
+                        parent,
+                        &asset_server,
+                        (
+                            PauseAction::Resume,
+                            interaction::IaMenuEntry,
+                            interaction::IaDefaultFocus,
+                            Focusable::default(),
+                        ),
+                        element::ElementInitParams::MenuEntry {
+                            icon: String::from("play-light"),
+                            text: String::from("RESUME"),
+                        },
+                    );
+                    let entries = [
+                        (PauseAction::Restart, "arrow-counter-clockwise", "RESTART"),
+                        (
+                            PauseAction::MoveToPage(PageState::Level),
+                            "circles-four-light",
+                            "LEVEL SELECT",
+                        ),
+                        (PauseAction::Settings, "gear-light", "SETTINGS"),
+                    ];
+                    for (action, icon, text) in entries {
+                        element::build_element(
+                            parent,
+                            &asset_server,
+                            (action, interaction::IaMenuEntry, Focusable::default()),
+                            element::ElementInitParams::MenuEntry {
+                                icon: String::from(icon),
+                                text: String::from(text),
+                            },
+                        );
+                    }
+                });
+        });
+}
+
+fn handle_resume_key(
+    key_input: Res<ButtonInput<KeyCode>>,
+    mut game_phase: ResMut<NextState<PhaseState>>,
+) {
+    if key_input.just_pressed(KeyCode::Escape) {
+        game_phase.set(PhaseState::Preparing);
+    }
+}
+
+// NOTE: Resuming and the settings keep the level, the others drop the mode kept aside
+fn handle_pause_navigation(
+    mut actions: Query<&mut PauseAction>,
+    mut events: EventReader<NavEvent>,
+    mut game_status: ResMut<GameStatus>,
+    mut game_phase: ResMut<NextState<PhaseState>>,
+    mut page_state: ResMut<NextState<PageState>>,
+) {
+    events.nav_iter().activated_in_query_foreach_mut(
+        &mut actions,
+        |mut action| match &mut *action {
+            PauseAction::Resume => game_phase.set(PhaseState::Preparing),
+            PauseAction::Restart => {
+                game_status.resume_mode = None;
+                game_phase.set(PhaseState::Preparing);
+            }
+            PauseAction::Settings => {
+                game_status.is_in_pause_settings = true;
+                page_state.set(PageState::SettingsAudio);
+            }
+            PauseAction::MoveToPage(state) => {
+                game_status.resume_mode = None;
+                page_state.set(*state);
+            }
+        },
+    );
+}
+
+fn state_exit(
+    mut commands: Commands,
+    dyn_query: Query<Entity, With<GameDyn>>,
+    mut game_status: ResMut<GameStatus>,
+    mut key_binding: ResMut<key_binding::KeyBindingConfig>,
+) {
+    key_binding.mode = key_binding::KeyBindingMode::Gaming;
+    if game_status.resume_mode.is_some() {
+        return;
+    }
+    game_status.mode = StatusMode::Demo;
+    let dyn_entity = dyn_query.get_single().unwrap();
+    commands
+        .get_entity(dyn_entity)
+        .unwrap()
+        .despawn_descendants();
+}
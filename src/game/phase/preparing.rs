@@ -1,11 +1,19 @@
 use self::{
+    aiming::{handle_aiming, launch, refresh_aim_guide, refresh_control_displays},
     ball::Ball,
-    ball_interaction::{build_hit_map, calcuate_v_after_hit, HitAction},
+    debug_overlay::DebugOverlay,
+    ghost::{build_ghost, refresh_ghost_balls, spawn_ghost_balls},
+    kinematics::KinematicsRecord,
+    placing::{handle_placing, refresh_placement_guide, sync_placement},
+    simulation::{ControlValues, HitEvent, Simulation, SimulationOutcome},
+    time_scale::{sync_ball_animators, TimeScale},
+    trajectory::refresh_trajectory_preview,
+    wall::Wall,
 };
 use super::*;
 use crate::app::{
     anime_effect::{self, ANIME_EFFECT_DONE_EVENT},
-    audio, key_binding, settings,
+    audio, key_binding, settings, ui,
 };
 use bevy_persistent::prelude::*;
 use bevy_tweening::{component_animator_system, TweenCompleted};
@@ -23,6 +31,19 @@ impl PhaseBase for Phase {
                 Update,
                 (
                     state_update,
+                    handle_level_reload,
+                    handle_aiming,
+                    handle_replay,
+                    handle_time_scale.before(handle_aiming),
+                    handle_pause,
+                    handle_placing,
+                    sync_placement,
+                    refresh_placement_guide,
+                    refresh_control_displays,
+                    refresh_aim_guide,
+                    refresh_trajectory_preview,
+                    refresh_ghost_balls,
+                    sync_ball_animators.before(component_animator_system::<Ball>),
                     component_animator_system::<Ball>,
                     component_animator_system::<anime_effect::AnimeEffect>,
                 )
@@ -30,7 +51,10 @@ impl PhaseBase for Phase {
             )
             .add_systems(
                 OnExit(self.state()),
-                (anime_effect::clear_anime_effect, state_exit),
+                (
+                    anime_effect::clear_anime_effect.run_if(is_leaving_level),
+                    state_exit,
+                ),
             );
     }
 }
@@ -38,19 +62,66 @@ impl PhaseBase for Phase {
 fn state_enter(
     mut commands: Commands,
     dyn_query: Query<Entity, With<GameDyn>>,
+    level_assets: Res<Assets<level_builder::LevelConfig>>,
+    catalog_assets: Res<Assets<level_builder::LevelCatalog>>,
+    leaderboard: Res<Persistent<Leaderboard>>,
+    settings: Res<Persistent<settings::Settings>>,
     mut game_status: ResMut<GameStatus>,
     mut key_binding: ResMut<key_binding::KeyBindingConfig>,
 ) {
-    game_status.mode = StatusMode::Setup;
     key_binding.mode = key_binding::KeyBindingMode::Gaming;
+    // NOTE: Resuming from the pause keeps the level as it was left
+    if let Some(mode) = game_status.resume_mode.take() {
+        game_status.mode = mode;
+        game_status.require_refresh(Some(ui::REFRESH_GAME_FG));
+        return;
+    }
+    game_status.mode = StatusMode::Setup;
     let dyn_entity = dyn_query.get_single().unwrap();
+    commands
+        .get_entity(dyn_entity)
+        .unwrap()
+        .despawn_descendants();
+    // NOTE: Play the first level in the catalogue if no level is chosen
+    if game_status.level_code.is_empty() {
+        if let Some(level_code) = level_builder::level_codes(&catalog_assets).first() {
+            game_status.level_code = level_code.clone();
+        }
+    }
+    if let Some((_, level_config)) =
+        level_builder::fetch_level(&level_assets, &game_status.level_code)
+    {
+        setup_level(&mut commands, dyn_entity, level_config, &mut game_status);
+        apply_retry(&mut game_status);
+        apply_replay(&mut game_status);
+        setup_ghost(
+            &mut commands,
+            dyn_entity,
+            &mut game_status,
+            &leaderboard,
+            &settings,
+        );
+    } else {
+        error!("level {} is not loaded", game_status.level_code);
+    }
+}
+
+fn setup_level(
+    commands: &mut Commands,
+    dyn_entity: Entity,
+    level_config: &level_builder::LevelConfig,
+    game_status: &mut GameStatus,
+) {
+    game_status.current_level = level_config.clone();
+    game_status.reset_history();
+    game_status.control_values = ControlValues::from_level(level_config);
+    game_status.simulation = Simulation::new(level_config, &game_status.control_values);
+    game_status.ghost = None;
     let mut entity_commands = commands.get_entity(dyn_entity).unwrap();
-    entity_commands.despawn_descendants();
     entity_commands.with_children(|parent| {
-        let ball_configs = level_builder::LEVEL_MAP.get("hit_test").unwrap();
-        // let ball_configs = level_builder::LEVEL_MAP.get("simple").unwrap();
-        for ball_config in ball_configs.balls.iter() {
+        for (index, ball_config) in level_config.balls.iter().enumerate() {
             Ball::create_sprite(
+                index,
                 ball_config.ball_type.clone(),
                 parent,
                 {},
@@ -58,9 +129,170 @@ fn state_enter(
                 ball_config.control_params.clone(),
             );
         }
+        for zone in level_config.zones.iter() {
+            zone.create_sprite(parent);
+        }
+        for wall in level_config.walls.iter() {
+            Wall::create_sprite(parent, wall.clone());
+        }
     });
 }
 
+fn handle_level_reload(
+    mut commands: Commands,
+    mut level_events: EventReader<AssetEvent<level_builder::LevelConfig>>,
+    dyn_query: Query<Entity, With<GameDyn>>,
+    level_assets: Res<Assets<level_builder::LevelConfig>>,
+    leaderboard: Res<Persistent<Leaderboard>>,
+    settings: Res<Persistent<settings::Settings>>,
+    mut game_status: ResMut<GameStatus>,
+) {
+    for event in level_events.read() {
+        if let AssetEvent::Modified { id } = event {
+            if game_status.mode != StatusMode::Setup && game_status.mode != StatusMode::Deploying {
+                continue;
+            }
+            if let Some(level_config) = level_assets.get(*id) {
+                if level_config.code != game_status.current_level.code {
+                    continue;
+                }
+                game_status.mode = StatusMode::Setup;
+                let dyn_entity = dyn_query.get_single().unwrap();
+                commands
+                    .get_entity(dyn_entity)
+                    .unwrap()
+                    .despawn_descendants();
+                setup_level(&mut commands, dyn_entity, level_config, &mut game_status);
+                apply_replay(&mut game_status);
+                setup_ghost(
+                    &mut commands,
+                    dyn_entity,
+                    &mut game_status,
+                    &leaderboard,
+                    &settings,
+                );
+            }
+        }
+    }
+}
+
+// NOTE: Replays show the recorded attempt only, so the ghost is for live attempts
+fn setup_ghost(
+    commands: &mut Commands,
+    dyn_entity: Entity,
+    game_status: &mut GameStatus,
+    leaderboard: &Leaderboard,
+    settings: &settings::Settings,
+) {
+    if game_status.replay.is_some() {
+        return;
+    }
+    game_status.ghost = build_ghost(&game_status.current_level, leaderboard, settings);
+    if let Some(ghost) = &game_status.ghost {
+        commands.entity(dyn_entity).with_children(|parent| {
+            spawn_ghost_balls(parent, ghost);
+        });
+    }
+}
+
+// NOTE: Retries start from the last controls if they still fit the level
+fn apply_retry(game_status: &mut GameStatus) {
+    if let Some(values) = game_status.retry_values.take() {
+        if values.fits_level(&game_status.current_level) {
+            game_status.control_values = values;
+        }
+    }
+}
+
+// NOTE: Replays skip deploying and launch with the recorded controls
+fn apply_replay(game_status: &mut GameStatus) {
+    let level = &game_status.current_level;
+    if let Some(replay) = game_status.replay.as_mut() {
+        replay.restart();
+        replay.check_layout(level);
+        game_status.control_values = replay.controls(level);
+        game_status.require_refresh(Some(ui::REFRESH_GAME_FG));
+    }
+}
+
+fn handle_replay(
+    mut commands: Commands,
+    key_input: Res<ButtonInput<KeyCode>>,
+    dyn_query: Query<Entity, With<GameDyn>>,
+    level_assets: Res<Assets<level_builder::LevelConfig>>,
+    mut game_status: ResMut<GameStatus>,
+) {
+    if game_status.replay.is_none() {
+        return;
+    }
+    if key_input.just_pressed(KeyCode::KeyR) {
+        if let Some((_, level_config)) =
+            level_builder::fetch_level(&level_assets, &game_status.current_level.code)
+        {
+            game_status.mode = StatusMode::Setup;
+            let dyn_entity = dyn_query.get_single().unwrap();
+            commands
+                .get_entity(dyn_entity)
+                .unwrap()
+                .despawn_descendants();
+            setup_level(&mut commands, dyn_entity, level_config, &mut game_status);
+            apply_replay(&mut game_status);
+        }
+    }
+}
+
+// NOTE: Space stops a running attempt, the period key then steps it one tick at a time. The stop
+// is lifted once the attempt is over so the next one runs again.
+fn handle_time_scale(
+    key_input: Res<ButtonInput<KeyCode>>,
+    mut time_scale: ResMut<TimeScale>,
+    mut game_status: ResMut<GameStatus>,
+) {
+    let is_running = game_status.mode == StatusMode::Running;
+    let mut is_changed = true;
+    if !is_running && time_scale.is_stopped() {
+        time_scale.toggle_stop();
+    } else if key_input.just_pressed(KeyCode::Space) {
+        if is_running {
+            time_scale.toggle_stop();
+        } else {
+            is_changed = false;
+        }
+    } else if key_input.any_just_pressed([KeyCode::Equal, KeyCode::BracketRight]) {
+        time_scale.speed_up();
+    } else if key_input.any_just_pressed([KeyCode::Minus, KeyCode::BracketLeft]) {
+        time_scale.slow_down();
+    } else if key_input.just_pressed(KeyCode::Digit0) {
+        time_scale.reset();
+    } else {
+        is_changed = false;
+        if key_input.just_pressed(KeyCode::Period) && time_scale.is_stopped() {
+            time_scale.request_frame_step();
+        }
+    }
+    if is_changed {
+        game_status.require_refresh(Some(ui::REFRESH_GAME_FG));
+    }
+}
+
+// NOTE: The mode is kept aside while paused, the level is left untouched until resumed
+fn handle_pause(
+    key_input: Res<ButtonInput<KeyCode>>,
+    mut game_status: ResMut<GameStatus>,
+    mut game_phase: ResMut<NextState<PhaseState>>,
+) {
+    if !key_input.just_pressed(KeyCode::Escape) {
+        return;
+    }
+    if matches!(
+        game_status.mode,
+        StatusMode::Deploying | StatusMode::Running
+    ) {
+        game_status.resume_mode = Some(game_status.mode);
+        game_phase.set(PhaseState::Paused);
+    }
+}
+
 fn state_update(
     mut commands: Commands,
     mut ball_query: Query<(Entity, &mut Ball, &mut Transform), With<Ball>>,
@@ -71,74 +303,70 @@ fn state_update(
     time: Res<Time>,
     settings: Res<Persistent<settings::Settings>>,
     asset_server: Res<AssetServer>,
-    mut ball_tick: Local<u8>,
+    mut time_scale: ResMut<TimeScale>,
+    mut debug_overlay: ResMut<DebugOverlay>,
+    mut kinematics: ResMut<KinematicsRecord>,
     mut game_status: ResMut<GameStatus>,
+    mut leaderboard: ResMut<Persistent<Leaderboard>>,
+    mut game_phase: ResMut<NextState<PhaseState>>,
 ) {
     if refresh_timer.0.tick(time.delta()).just_finished() {
-        if *ball_tick == 0 {
-            let hit_map = build_hit_map(&ball_query);
-            // NOTE: Detect success and failure first
-            for (e, _, _) in ball_query.iter_mut() {
-                if let Some(actions) = hit_map.get(&e) {
-                    for action in actions.iter() {
-                        match action {
-                            HitAction::Success => {
-                                // success
-                            }
-                            HitAction::Failure => {
-                                // failure
-                            }
-                            _ => (),
-                        }
-                    }
+        let steps = match game_status.mode {
+            StatusMode::Running => time_scale.steps_for_tick(),
+            _ => 0,
+        };
+        for _ in 0..steps {
+            if let Some(ghost) = game_status.ghost.as_mut() {
+                if ghost.outcome().is_none() {
+                    ghost.step();
                 }
             }
-            // NOTE: Handle ball hit
-            for (e, mut b, _) in ball_query.iter_mut() {
-                let mut is_hit: bool = false;
-                let mut is_no_hit_detected: bool = true;
-                let mut bp = b.property.clone();
-                if let Some(actions) = hit_map.get(&e) {
-                    for action in actions.iter() {
-                        match action {
-                            HitAction::Move(info) => {
-                                if b.check_hit_window(info.opponent_entity) {
-                                    let new_v = calcuate_v_after_hit(
-                                        &info.hit_type,
-                                        &bp,
-                                        &info.opponent_property,
-                                    );
-                                    bp.pos = bp.pos + new_v;
-                                    bp.v = new_v;
-                                    b.store_hit_entity(info.opponent_entity);
-                                    is_hit = true
-                                }
-                                is_no_hit_detected = false;
+            let result = game_status.simulation.step();
+            debug_overlay.record(game_status.simulation.time(), &result.events);
+            kinematics.record(
+                &game_status.current_level.code,
+                &game_status.simulation,
+                &result.events,
+            );
+            for event in result.events.iter() {
+                match event {
+                    HitEvent::Success { ball: index } | HitEvent::Failure { ball: index } => {
+                        for (_, mut ball, _) in ball_query.iter_mut() {
+                            if ball.index == *index && ball.state == BallState::Running {
+                                ball.trigger_ending(&mut commands);
                             }
-                            _ => (),
                         }
                     }
+                    HitEvent::Bounce { .. } | HitEvent::WallBounce { .. } => {
+                        audio::play_se("hit", &mut commands, &asset_server, settings.as_ref());
+                    }
                 }
-                if is_hit && b.property.movement_type == BallMovementType::Movable {
-                    b.update_pos(bp.pos);
-                    b.update_v(bp.v);
-                    audio::play_se("hit", &mut commands, &asset_server, settings.as_ref());
-                }
-                if is_no_hit_detected {
-                    b.clear_hit_counter();
-                }
+            }
+            game_status.current_history.collisions += result.contacts;
+            if let Some(outcome) = result.outcome {
+                finish_level(
+                    outcome == SimulationOutcome::Success,
+                    &mut commands,
+                    &asset_server,
+                    settings.as_ref(),
+                    &mut game_status,
+                    &mut leaderboard,
+                );
+                return;
             }
             for (_, mut ball, mut trans) in ball_query.iter_mut() {
-                if ball.state == BallState::Running
-                    && ball.property.movement_type == BallMovementType::Movable
+                if ball.state != BallState::Running
+                    || ball.property.movement_type != BallMovementType::Movable
                 {
-                    ball.travel();
+                    continue;
+                }
+                if let Some(sim_ball) = game_status.simulation.ball(ball.index) {
+                    ball.update_pos(sim_ball.property.pos);
+                    ball.update_v(sim_ball.property.v);
                     trans.translation = ball.property.pos.extend(0.0);
                 }
             }
-            *ball_tick = 1;
-        } else {
-            *ball_tick -= 1;
+            game_status.current_history.time = game_status.simulation.time();
         }
         for (_, mut ball, _) in ball_query.iter_mut() {
             if ball.state == BallState::Created {
@@ -153,7 +381,14 @@ fn state_update(
         let mut entities_to_despawn: HashSet<Entity> = HashSet::new();
         for tween_event in tween_completed_events.read() {
             if tween_event.user_data == STARTING_DONE_EVENT {
-                game_status.mode = StatusMode::Deploying;
+                if game_status.mode == StatusMode::Setup {
+                    if game_status.replay.is_some() {
+                        launch(&mut game_status);
+                    } else {
+                        game_status.mode = StatusMode::Deploying;
+                    }
+                }
+                start_running(&mut ball_query, tween_event.entity);
             }
             if tween_event.user_data == ENDING_DONE_EVENT
                 || tween_event.user_data == ANIME_EFFECT_DONE_EVENT
@@ -166,15 +401,65 @@ fn state_update(
                 entity_commands.despawn_recursive()
             }
         }
+        // NOTE: Move to game over after all ending animations are done
+        if game_status.mode == StatusMode::Done
+            && ball_query
+                .iter()
+                .all(|(e, b, _)| b.state != BallState::Ending || entities_to_despawn.contains(&e))
+        {
+            game_phase.set(PhaseState::GameOver);
+        }
     }
 }
 
+fn finish_level(
+    is_clear: bool,
+    commands: &mut Commands,
+    asset_server: &Res<AssetServer>,
+    settings: &Persistent<settings::Settings>,
+    game_status: &mut ResMut<GameStatus>,
+    leaderboard: &mut ResMut<Persistent<Leaderboard>>,
+) {
+    game_status.mode = StatusMode::Done;
+    if is_clear {
+        audio::play_se("success", commands, asset_server, settings);
+    } else {
+        audio::play_se("failure", commands, asset_server, settings);
+    }
+    game_status.sumbit_history(is_clear);
+    game_status.current_history.stars = par::rate_history(
+        game_status.current_level.par.as_ref(),
+        &game_status.current_history,
+    );
+    // NOTE: Replays are verified against the record instead of being stored again, and test plays
+    // from the editor are not stored at all
+    let time = game_status.current_history.time;
+    if let Some(replay) = game_status.replay.as_mut() {
+        replay.verify(is_clear, time);
+    }
+    if game_status.replay.is_some() || game_status.is_test_play {
+        game_status.require_refresh(Some(ui::REFRESH_GAME_FG));
+        return;
+    }
+    let level_code = game_status.current_level.code.clone();
+    let history = game_status.current_history.clone();
+    leaderboard
+        .update(|leaderboard| {
+            leaderboard.open_level(level_code.clone());
+            leaderboard.store_level_history(level_code.clone(), history.clone());
+        })
+        .expect("failed to update leaderboard");
+}
+
 fn state_exit(
     mut commands: Commands,
     dyn_query: Query<Entity, With<GameDyn>>,
     mut game_status: ResMut<GameStatus>,
     mut key_binding: ResMut<key_binding::KeyBindingConfig>,
 ) {
+    if game_status.resume_mode.is_some() {
+        return;
+    }
     game_status.mode = StatusMode::Demo;
     key_binding.mode = key_binding::KeyBindingMode::Gaming;
     let dyn_entity = dyn_query.get_single().unwrap();
@@ -182,11 +467,14 @@ fn state_exit(
     entity_commands.despawn_descendants();
 }
 
-fn start_running() {
-    // game_status.mode = StatusMode::Running;
-    // for (e, mut ball, _) in ball_query.iter_mut() {
-    //     if e == tween_event.entity {
-    //         ball.trigger_anime(BallState::Running);
-    //     }
-    // }
+// NOTE: Balls wait for the launch in the simulation while deploying
+fn start_running(
+    ball_query: &mut Query<(Entity, &mut Ball, &mut Transform), With<Ball>>,
+    entity: Entity,
+) {
+    if let Ok((_, mut ball, _)) = ball_query.get_mut(entity) {
+        if ball.state == BallState::Starting {
+            ball.trigger_anime(BallState::Running);
+        }
+    }
 }
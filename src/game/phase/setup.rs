@@ -28,10 +28,16 @@ fn state_enter(
     startup(&mut commands, &mut game_status);
 }
 
+// NOTE: Wait for the level files so the first page finds the catalogue
 fn state_update(
+    asset_server: Res<AssetServer>,
+    level_folder: Res<level_builder::LevelFolder>,
     mut game_phase: ResMut<NextState<PhaseState>>,
     mut book_page: ResMut<NextState<PageState>>,
 ) {
+    if !level_folder.is_settled(&asset_server) {
+        return;
+    }
     game_phase.set(PhaseState::Ready);
     // TODO: Test
     // book_page.set(PageState::Menu);
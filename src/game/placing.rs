@@ -0,0 +1,166 @@
+use super::{
+    aiming::launchable_stones,
+    ball_interaction::{detect_hit, HitType},
+    level_builder::LevelConfig,
+    simulation::{ControlField, ControlValues, Simulation},
+    *,
+};
+use crate::app::{cursor::AppCursorData, ui};
+use bevy_prototype_lyon::prelude::*;
+
+const GUIDE_LINE_W: f32 = ui::FONT_SIZE / 18.0;
+const GUIDE_Z_LAYER: f32 = 0.7;
+
+#[derive(Component)]
+pub struct PlacementGuide;
+
+pub fn handle_placing(
+    mouse_input: Res<ButtonInput<MouseButton>>,
+    cursor_data: Res<AppCursorData>,
+    mut game_status: ResMut<GameStatus>,
+    mut dragging: Local<Option<usize>>,
+) {
+    if game_status.mode != StatusMode::Deploying {
+        *dragging = None;
+        return;
+    }
+    let level = game_status.current_level.clone();
+    let simulation = Simulation::new(&level, &game_status.control_values);
+    if mouse_input.just_pressed(MouseButton::Left) {
+        *dragging = placeable_balls(&level).into_iter().find(|index| {
+            simulation.ball(*index).map_or(false, |ball| {
+                cursor_data.canvas_pos.distance(ball.property.pos) <= ball.property.radius
+            })
+        });
+    }
+    if mouse_input.just_released(MouseButton::Left) {
+        *dragging = None;
+    }
+    let Some(ball) = *dragging else {
+        return;
+    };
+    let mut values = game_status.control_values.clone();
+    values.set(&level, ball, ControlField::X, cursor_data.canvas_pos.x);
+    values.set(&level, ball, ControlField::Y, cursor_data.canvas_pos.y);
+    if values == game_status.control_values {
+        return;
+    }
+    // NOTE: Keep the last placement if the ball would overlap another one
+    let moved = Simulation::new(&level, &values);
+    let property = &moved.balls()[ball].property;
+    let is_overlapped = moved.balls().iter().enumerate().any(|(index, other)| {
+        index != ball && detect_hit(property, &other.property) != HitType::None
+    });
+    if !is_overlapped {
+        game_status.control_values = values;
+    }
+}
+
+// NOTE: Move the placeable balls to their chosen positions while deploying
+pub fn sync_placement(
+    mut ball_query: Query<(&mut Ball, &mut Transform)>,
+    game_status: Res<GameStatus>,
+    mut last_values: Local<Option<(String, ControlValues)>>,
+) {
+    if game_status.mode != StatusMode::Deploying && game_status.mode != StatusMode::Setup {
+        *last_values = None;
+        return;
+    }
+    let values_key = Some((
+        game_status.current_level.code.clone(),
+        game_status.control_values.clone(),
+    ));
+    if *last_values == values_key {
+        return;
+    }
+    *last_values = values_key;
+    let level = &game_status.current_level;
+    let simulation = Simulation::new(level, &game_status.control_values);
+    let placeable = placeable_balls(level);
+    for (mut ball, mut trans) in ball_query.iter_mut() {
+        if !placeable.contains(&ball.index) {
+            continue;
+        }
+        if let Some(sim_ball) = simulation.ball(ball.index) {
+            ball.property.pos = sim_ball.property.pos;
+            trans.translation = ball.property.pos.extend(0.0);
+        }
+    }
+}
+
+pub fn refresh_placement_guide(
+    mut commands: Commands,
+    dyn_query: Query<Entity, With<GameDyn>>,
+    guide_query: Query<Entity, With<PlacementGuide>>,
+    game_status: Res<GameStatus>,
+    mut last_level: Local<Option<String>>,
+) {
+    let is_shown = game_status.mode == StatusMode::Deploying;
+    let level_key = Some(game_status.current_level.code.clone());
+    if is_shown && *last_level == level_key && !guide_query.is_empty() {
+        return;
+    }
+    for entity in guide_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    if !is_shown {
+        *last_level = None;
+        return;
+    }
+    *last_level = level_key;
+    let Ok(dyn_entity) = dyn_query.get_single() else {
+        return;
+    };
+    let level = &game_status.current_level;
+    commands.entity(dyn_entity).with_children(|parent| {
+        parent
+            .spawn((
+                SpatialBundle {
+                    transform: Transform::from_xyz(0.0, 0.0, GUIDE_Z_LAYER),
+                    ..default()
+                },
+                PlacementGuide,
+            ))
+            .with_children(|parent| {
+                for index in placeable_balls(level) {
+                    let ball_config = &level.balls[index];
+                    let pos = ball_config.property.pos;
+                    let (x_min, x_max) = ball_config.control_params.x.unwrap_or((pos.x, pos.x));
+                    let (y_min, y_max) = ball_config.control_params.y.unwrap_or((pos.y, pos.y));
+                    let mut path_builder = PathBuilder::new();
+                    path_builder.move_to(Vec2::new(x_min, y_min));
+                    path_builder.line_to(Vec2::new(x_max, y_min));
+                    path_builder.line_to(Vec2::new(x_max, y_max));
+                    path_builder.line_to(Vec2::new(x_min, y_max));
+                    path_builder.close();
+                    parent.spawn((
+                        ShapeBundle {
+                            path: path_builder.build(),
+                            ..default()
+                        },
+                        Fill::color(ball_config.ball_type.color().with_alpha(0.05)),
+                        Stroke {
+                            color: ball_config.ball_type.color().with_alpha(0.4),
+                            options: StrokeOptions::DEFAULT
+                                .with_line_width(GUIDE_LINE_W)
+                                .with_line_cap(LineCap::Round),
+                        },
+                    ));
+                }
+            });
+    });
+}
+
+pub fn placeable_balls(level: &LevelConfig) -> Vec<usize> {
+    let launchable = launchable_stones(level);
+    level
+        .balls
+        .iter()
+        .enumerate()
+        .filter(|(index, ball)| {
+            !launchable.contains(index)
+                && (ball.control_params.x.is_some() || ball.control_params.y.is_some())
+        })
+        .map(|(index, _)| index)
+        .collect()
+}
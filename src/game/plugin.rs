@@ -5,6 +5,10 @@ pub struct GamePlugin;
 impl Plugin for GamePlugin {
     fn build(&self, app: &mut App) {
         app.insert_resource(status::GameStatus::default())
+            .insert_resource(editor::EditorStatus::default())
+            .insert_resource(time_scale::TimeScale::default())
+            .insert_resource(debug_overlay::DebugOverlay::default())
+            .insert_resource(kinematics::KinematicsRecord::default())
             .insert_resource(timer::GameRefreshTimer(Timer::from_seconds(
                 timer::GAME_REFRESH_FRAME_SECS,
                 TimerMode::Repeating,
@@ -21,8 +25,20 @@ impl Plugin for GamePlugin {
                 timer::GAME_SCOREBOARD_SECS,
                 TimerMode::Repeating,
             )))
+            .init_asset::<level_builder::LevelConfig>()
+            .init_asset_loader::<level_builder::LevelConfigLoader>()
+            .init_asset::<level_builder::LevelCatalog>()
+            .init_asset_loader::<level_builder::LevelCatalogLoader>()
             .init_state::<PhaseState>()
-            .add_systems(Update, (field::refresh_field,))
+            .add_systems(Startup, level_builder::load_levels)
+            .add_systems(
+                Update,
+                (
+                    field::refresh_field,
+                    debug_overlay::handle_debug_toggle,
+                    debug_overlay::refresh_debug_overlay,
+                ),
+            )
             .add_plugins((leaderboard::LeaderboardPlugin,));
         for phase in phase::PHASES {
             phase.build(app);
@@ -0,0 +1,278 @@
+use super::{
+    level_builder::{fetch_level, LevelConfig},
+    simulation::ControlValues,
+    LevelHistory,
+};
+use crate::app;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt, fs,
+    path::{Path, PathBuf},
+};
+use thiserror::Error;
+
+const REPLAY_FILE_VERSION: u8 = 1;
+const REPLAY_FOLDER: &str = "replays";
+pub const REPLAY_EXT: &str = "replay.ron";
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ReplayDivergence {
+    Layout,
+    Outcome { recorded: bool, replayed: bool },
+    Time { recorded: u32, replayed: u32 },
+}
+
+impl fmt::Display for ReplayDivergence {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ReplayDivergence::Layout => write!(f, "LEVEL CHANGED"),
+            ReplayDivergence::Outcome { recorded, .. } => {
+                if *recorded {
+                    write!(f, "CLEAR NOT REPRODUCED")
+                } else {
+                    write!(f, "FAILURE NOT REPRODUCED")
+                }
+            }
+            ReplayDivergence::Time { recorded, replayed } => {
+                write!(f, "TIME {} != {}", replayed, recorded)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ReplayStatus {
+    pub history: LevelHistory,
+    pub divergence: Option<ReplayDivergence>,
+    pub is_verified: bool,
+}
+
+impl ReplayStatus {
+    pub fn new(history: LevelHistory) -> Self {
+        Self {
+            history,
+            divergence: None,
+            is_verified: false,
+        }
+    }
+    pub fn controls(&self, level: &LevelConfig) -> ControlValues {
+        let values = ControlValues::from(&self.history);
+        if values.fits_level(level) {
+            values
+        } else {
+            ControlValues::from_level(level)
+        }
+    }
+    pub fn check_layout(&mut self, level: &LevelConfig) {
+        if !ControlValues::from(&self.history).fits_level(level) {
+            self.divergence = Some(ReplayDivergence::Layout);
+        }
+    }
+    pub fn restart(&mut self) {
+        self.divergence = None;
+        self.is_verified = false;
+    }
+    pub fn verify(&mut self, is_clear: bool, time: u32) {
+        self.is_verified = true;
+        if self.divergence.is_some() {
+            return;
+        }
+        if is_clear != self.history.is_clear {
+            self.divergence = Some(ReplayDivergence::Outcome {
+                recorded: self.history.is_clear,
+                replayed: is_clear,
+            });
+        } else if time != self.history.time {
+            self.divergence = Some(ReplayDivergence::Time {
+                recorded: self.history.time,
+                replayed: time,
+            });
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum ReplayFileError {
+    #[error("could not access the replay file: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("could not parse the replay file: {0}")]
+    Ron(#[from] ron::error::SpannedError),
+    #[error("could not write the replay file: {0}")]
+    Serialize(#[from] ron::Error),
+    #[error("the replay file version {0} is not supported")]
+    UnsupportedVersion(u8),
+    #[error("the level {0} is not installed")]
+    UnknownLevel(String),
+    #[error("the level {0} is different from the one the replay was recorded on")]
+    LevelMismatch(String),
+}
+
+// NOTE: The level hash makes sure a shared replay is played on the same level content
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ReplayFile {
+    pub version: u8,
+    pub level: String,
+    pub level_hash: String,
+    pub history: LevelHistory,
+}
+
+impl ReplayFile {
+    pub fn new(level: &LevelConfig, history: &LevelHistory) -> Self {
+        Self {
+            version: REPLAY_FILE_VERSION,
+            level: level.code.clone(),
+            level_hash: format!("{:016x}", level.content_hash()),
+            history: history.clone(),
+        }
+    }
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ReplayFileError> {
+        let replay_file: ReplayFile = ron::de::from_bytes(bytes)?;
+        if replay_file.version != REPLAY_FILE_VERSION {
+            return Err(ReplayFileError::UnsupportedVersion(replay_file.version));
+        }
+        Ok(replay_file)
+    }
+    pub fn check_level(&self, level_assets: &Assets<LevelConfig>) -> Result<(), ReplayFileError> {
+        let Some((_, level)) = fetch_level(level_assets, &self.level) else {
+            return Err(ReplayFileError::UnknownLevel(self.level.clone()));
+        };
+        if format!("{:016x}", level.content_hash()) != self.level_hash {
+            return Err(ReplayFileError::LevelMismatch(self.level.clone()));
+        }
+        Ok(())
+    }
+}
+
+pub fn export_replay(
+    level: &LevelConfig,
+    history: &LevelHistory,
+) -> Result<PathBuf, ReplayFileError> {
+    let replay_dir = replay_dir_path();
+    fs::create_dir_all(&replay_dir)?;
+    let created_at: String = history
+        .created_at
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect();
+    let path = replay_dir.join(format!("{}_{}.{}", level.code, created_at, REPLAY_EXT));
+    let content = ron::ser::to_string_pretty(
+        &ReplayFile::new(level, history),
+        ron::ser::PrettyConfig::default(),
+    )?;
+    fs::write(&path, content)?;
+    Ok(path)
+}
+
+pub fn import_replay(
+    path: &Path,
+    level_assets: &Assets<LevelConfig>,
+) -> Result<ReplayFile, ReplayFileError> {
+    let replay_file = ReplayFile::from_bytes(&fs::read(path)?)?;
+    replay_file.check_level(level_assets)?;
+    Ok(replay_file)
+}
+
+fn replay_dir_path() -> PathBuf {
+    dirs::config_dir()
+        .map(|native_config_dir| native_config_dir.join(app::APP_CODE))
+        .unwrap_or(Path::new("local").join("configuration"))
+        .join(REPLAY_FOLDER)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::simulation::{ControlField, Simulation, SimulationOutcome};
+
+    const LEVEL: &str = r#"(
+        name: "TEST",
+        gravity: (0.0, 0.0),
+        balls: [
+            (ball_type: Stone, property: (pos: (0.0, 0.0), radius: 320.0, movement_type: FixedReversed)),
+            (
+                ball_type: Stone,
+                property: (pos: (-100.0, 0.0), v: (4.0, 0.0), radius: 10.0, movement_type: Movable),
+                control_params: (angle: Some((-45.0, 45.0))),
+            ),
+            (ball_type: Goal, property: (pos: (100.0, 40.0), radius: 30.0, movement_type: Fixed)),
+        ],
+    )"#;
+    const MAX_TICKS: u32 = 20000;
+
+    fn play(level: &LevelConfig, controls: &ControlValues) -> (bool, u32) {
+        let mut simulation = Simulation::new(level, controls);
+        simulation.start();
+        for _ in 0..MAX_TICKS {
+            if simulation.step().outcome.is_some() {
+                break;
+            }
+        }
+        (
+            simulation.outcome() == Some(SimulationOutcome::Success),
+            simulation.time(),
+        )
+    }
+
+    #[test]
+    fn replay_file_reproduces_the_attempt() {
+        let level = LevelConfig::from_bytes("test", LEVEL.as_bytes()).unwrap();
+        let mut controls = ControlValues::from_level(&level);
+        controls.set(&level, 1, ControlField::Angle, 11.0);
+        let (is_clear, time) = play(&level, &controls);
+        assert!(is_clear);
+        let history = LevelHistory {
+            is_clear,
+            time,
+            angle: controls.angle.clone(),
+            created_at: String::from("2024-01-01_00:00:00"),
+            ..default()
+        };
+        let content = ron::ser::to_string(&ReplayFile::new(&level, &history)).unwrap();
+        let replay_file = ReplayFile::from_bytes(content.as_bytes()).unwrap();
+        let mut replay = ReplayStatus::new(replay_file.history);
+        replay.check_layout(&level);
+        let (is_clear, time) = play(&level, &replay.controls(&level));
+        replay.verify(is_clear, time);
+        assert!(replay.is_verified);
+        assert_eq!(replay.divergence, None);
+    }
+
+    #[test]
+    fn replay_reports_where_it_diverges() {
+        let level = LevelConfig::from_bytes("test", LEVEL.as_bytes()).unwrap();
+        let controls = ControlValues::from_level(&level);
+        let (is_clear, time) = play(&level, &controls);
+        let history = LevelHistory {
+            is_clear,
+            time: time + 1,
+            angle: controls.angle.clone(),
+            ..default()
+        };
+        let mut replay = ReplayStatus::new(history.clone());
+        replay.verify(is_clear, time);
+        assert_eq!(
+            replay.divergence,
+            Some(ReplayDivergence::Time {
+                recorded: time + 1,
+                replayed: time
+            })
+        );
+        replay.restart();
+        replay.verify(!is_clear, time + 1);
+        assert_eq!(
+            replay.divergence,
+            Some(ReplayDivergence::Outcome {
+                recorded: is_clear,
+                replayed: !is_clear
+            })
+        );
+        let mut replay = ReplayStatus::new(LevelHistory {
+            angle: vec![],
+            ..history
+        });
+        replay.check_layout(&level);
+        assert_eq!(replay.divergence, Some(ReplayDivergence::Layout));
+        assert_eq!(replay.controls(&level), controls);
+    }
+}
@@ -0,0 +1,645 @@
+use super::{
+    ball::*,
+    ball_interaction::{
+        calcuate_v_after_hit, find_first_hit, find_first_wall_hit, HitAction, HitType,
+    },
+    force_field::ForceZone,
+    level_builder::LevelConfig,
+    LevelHistory,
+};
+use bevy::prelude::*;
+
+const MAX_HITS_PER_STEP: u8 = 16;
+const STOP_SPEED: f32 = 0.01;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SimulationOutcome {
+    Success,
+    Failure,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum HitEvent {
+    Bounce { ball: usize, opponent: usize },
+    WallBounce { ball: usize, wall: usize },
+    Success { ball: usize },
+    Failure { ball: usize },
+}
+
+// NOTE: A contact of two movable balls bounces both of them, so the contacts are counted apart
+// from the bounce events
+#[derive(Debug, Default)]
+pub struct StepResult {
+    pub events: Vec<HitEvent>,
+    pub contacts: u32,
+    pub outcome: Option<SimulationOutcome>,
+}
+
+// NOTE: Values are consumed in ball order by the balls which have the related control params,
+// the same layout as LevelHistory stores them.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ControlValues {
+    pub x: Vec<f32>,
+    pub y: Vec<f32>,
+    pub force: Vec<f32>,
+    pub angle: Vec<f32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ControlField {
+    X,
+    Y,
+    Force,
+    Angle,
+}
+
+impl ControlField {
+    pub fn range(&self, params: &BallControlParams) -> Option<(f32, f32)> {
+        match self {
+            ControlField::X => params.x,
+            ControlField::Y => params.y,
+            ControlField::Force => params.force,
+            ControlField::Angle => params.angle,
+        }
+    }
+}
+
+impl ControlValues {
+    // NOTE: Start from the values the level itself is built with
+    pub fn from_level(level: &LevelConfig) -> Self {
+        let mut values = Self::default();
+        for ball in level.balls.iter() {
+            let params = &ball.control_params;
+            let property = &ball.property;
+            if let Some(range) = params.x {
+                values.x.push(clamp_range(property.pos.x, range));
+            }
+            if let Some(range) = params.y {
+                values.y.push(clamp_range(property.pos.y, range));
+            }
+            if let Some(range) = params.force {
+                values.force.push(clamp_range(property.v.length(), range));
+            }
+            if let Some(range) = params.angle {
+                let angle = property.v.y.atan2(property.v.x).to_degrees();
+                values.angle.push(clamp_range(angle, range));
+            }
+        }
+        values
+    }
+    pub fn get(&self, level: &LevelConfig, ball: usize, field: ControlField) -> Option<f32> {
+        let slot = Self::slot(level, ball, field)?;
+        self.values(field).get(slot).copied()
+    }
+    pub fn set(&mut self, level: &LevelConfig, ball: usize, field: ControlField, value: f32) {
+        let Some(slot) = Self::slot(level, ball, field) else {
+            return;
+        };
+        let Some(range) = field.range(&level.balls[ball].control_params) else {
+            return;
+        };
+        if let Some(current) = self.values_mut(field).get_mut(slot) {
+            *current = clamp_range(value, range);
+        }
+    }
+    // NOTE: Where the ball is placed, resolved the same way the simulation places it
+    pub fn position(&self, level: &LevelConfig, ball: usize) -> Option<Vec2> {
+        let ball_config = level.balls.get(ball)?;
+        let params = &ball_config.control_params;
+        let mut pos = ball_config.property.pos;
+        if let (Some(range), Some(x)) = (params.x, self.get(level, ball, ControlField::X)) {
+            pos.x = clamp_range(x, range);
+        }
+        if let (Some(range), Some(y)) = (params.y, self.get(level, ball, ControlField::Y)) {
+            pos.y = clamp_range(y, range);
+        }
+        Some(pos)
+    }
+    // NOTE: Recorded values only fit a level with the same control layout
+    pub fn fits_level(&self, level: &LevelConfig) -> bool {
+        let expected = Self::from_level(level);
+        self.x.len() == expected.x.len()
+            && self.y.len() == expected.y.len()
+            && self.force.len() == expected.force.len()
+            && self.angle.len() == expected.angle.len()
+    }
+    fn slot(level: &LevelConfig, ball: usize, field: ControlField) -> Option<usize> {
+        field.range(&level.balls.get(ball)?.control_params)?;
+        Some(
+            level.balls[..ball]
+                .iter()
+                .filter(|ball| field.range(&ball.control_params).is_some())
+                .count(),
+        )
+    }
+    fn values(&self, field: ControlField) -> &Vec<f32> {
+        match field {
+            ControlField::X => &self.x,
+            ControlField::Y => &self.y,
+            ControlField::Force => &self.force,
+            ControlField::Angle => &self.angle,
+        }
+    }
+    fn values_mut(&mut self, field: ControlField) -> &mut Vec<f32> {
+        match field {
+            ControlField::X => &mut self.x,
+            ControlField::Y => &mut self.y,
+            ControlField::Force => &mut self.force,
+            ControlField::Angle => &mut self.angle,
+        }
+    }
+}
+
+impl From<&LevelHistory> for ControlValues {
+    fn from(history: &LevelHistory) -> Self {
+        Self {
+            x: history.x.clone(),
+            y: history.y.clone(),
+            force: history.force.clone(),
+            angle: history.angle.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SimBall {
+    pub ball_type: BallType,
+    pub property: BallProperty,
+    pub well: Option<WellParams>,
+    pub is_running: bool,
+}
+
+impl SimBall {
+    fn new(ball_type: BallType, property: BallProperty, well: Option<WellParams>) -> Self {
+        let well = if ball_type == BallType::Attractor {
+            Some(well.unwrap_or_default())
+        } else {
+            None
+        };
+        Self {
+            ball_type,
+            property,
+            well,
+            is_running: false,
+        }
+    }
+    fn travel(&mut self, duration: f32) {
+        if self.property.movement_type == BallMovementType::Movable {
+            self.property.pos += self.property.v * duration;
+            if self.property.damping > 0.0 {
+                self.property.v *= (1.0 - self.property.damping).powf(duration);
+                if self.property.v.length() < STOP_SPEED {
+                    self.property.v = Vec2::ZERO;
+                }
+            }
+        }
+    }
+    fn is_moving(&self) -> bool {
+        self.is_running
+            && self.property.movement_type == BallMovementType::Movable
+            && self.property.v != Vec2::ZERO
+    }
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct Simulation {
+    balls: Vec<SimBall>,
+    walls: Vec<(Vec2, Vec2)>,
+    gravity: Vec2,
+    zones: Vec<ForceZone>,
+    time: u32,
+    outcome: Option<SimulationOutcome>,
+}
+
+impl Simulation {
+    pub fn new(level: &LevelConfig, controls: &ControlValues) -> Self {
+        let mut x_values = controls.x.iter();
+        let mut y_values = controls.y.iter();
+        let mut force_values = controls.force.iter();
+        let mut angle_values = controls.angle.iter();
+        let balls = level
+            .balls
+            .iter()
+            .map(|ball_config| {
+                let params = &ball_config.control_params;
+                let mut property = ball_config.property.clone();
+                if let Some(range) = params.x {
+                    if let Some(x) = x_values.next() {
+                        property.pos.x = clamp_range(*x, range);
+                    }
+                }
+                if let Some(range) = params.y {
+                    if let Some(y) = y_values.next() {
+                        property.pos.y = clamp_range(*y, range);
+                    }
+                }
+                let force = params
+                    .force
+                    .and_then(|range| force_values.next().map(|f| clamp_range(*f, range)));
+                let angle = params
+                    .angle
+                    .and_then(|range| angle_values.next().map(|a| clamp_range(*a, range)));
+                property.v = apply_launch(property.v, force, angle);
+                SimBall::new(
+                    ball_config.ball_type.clone(),
+                    property,
+                    ball_config.well.clone(),
+                )
+            })
+            .collect();
+        Self {
+            balls,
+            walls: level
+                .walls
+                .iter()
+                .flat_map(|wall| wall.segments())
+                .collect(),
+            gravity: level.gravity,
+            zones: level.zones.clone(),
+            time: 0,
+            outcome: None,
+        }
+    }
+    pub fn balls(&self) -> &[SimBall] {
+        &self.balls
+    }
+    pub fn ball(&self, index: usize) -> Option<&SimBall> {
+        self.balls.get(index)
+    }
+    pub fn walls(&self) -> &[(Vec2, Vec2)] {
+        &self.walls
+    }
+    pub fn time(&self) -> u32 {
+        self.time
+    }
+    pub fn outcome(&self) -> Option<SimulationOutcome> {
+        self.outcome
+    }
+    pub fn start(&mut self) {
+        for ball in self.balls.iter_mut() {
+            ball.is_running = true;
+        }
+    }
+    pub fn step(&mut self) -> StepResult {
+        let mut result = StepResult::default();
+        if self.outcome.is_some() {
+            return result;
+        }
+        if !self.balls.iter().any(|ball| ball.is_running) {
+            return result;
+        }
+        self.apply_forces();
+        let was_moving = self.balls.iter().any(|ball| ball.is_moving());
+        // NOTE: Move balls to each contact in order within the tick and reflect them there
+        let mut remaining: f32 = 1.0;
+        for _ in 0..MAX_HITS_PER_STEP {
+            let hit = find_first_hit(&self.balls, remaining);
+            let wall_hit = find_first_wall_hit(&self.balls, &self.walls, remaining);
+            if let Some(wall_hit) = wall_hit {
+                if hit.as_ref().map_or(true, |hit| wall_hit.time < hit.time) {
+                    self.travel(wall_hit.time);
+                    remaining -= wall_hit.time;
+                    // NOTE: Walls reflect balls like a Fixed ball with zero radius at the contact
+                    let contact_bp = BallProperty::fixed_point(wall_hit.contact);
+                    let ball = &mut self.balls[wall_hit.ball];
+                    ball.property.v =
+                        calcuate_v_after_hit(&HitType::Outside, &ball.property, &contact_bp);
+                    result.events.push(HitEvent::WallBounce {
+                        ball: wall_hit.ball,
+                        wall: wall_hit.wall,
+                    });
+                    result.contacts += 1;
+                    continue;
+                }
+            }
+            let Some(hit) = hit else {
+                break;
+            };
+            self.travel(hit.time);
+            remaining -= hit.time;
+            match hit.action {
+                HitAction::Success | HitAction::Failure => {
+                    let outcome = if hit.action == HitAction::Success {
+                        SimulationOutcome::Success
+                    } else {
+                        SimulationOutcome::Failure
+                    };
+                    for ball in [hit.b1, hit.b2] {
+                        self.balls[ball].is_running = false;
+                        result.events.push(match outcome {
+                            SimulationOutcome::Success => HitEvent::Success { ball },
+                            SimulationOutcome::Failure => HitEvent::Failure { ball },
+                        });
+                    }
+                    self.time += 1;
+                    self.outcome = Some(outcome);
+                    result.outcome = Some(outcome);
+                    return result;
+                }
+                HitAction::Move => {
+                    let bp1 = self.balls[hit.b1].property.clone();
+                    let bp2 = self.balls[hit.b2].property.clone();
+                    result.contacts += 1;
+                    for (ball, opponent, bp, opponent_bp) in
+                        [(hit.b1, hit.b2, &bp1, &bp2), (hit.b2, hit.b1, &bp2, &bp1)]
+                    {
+                        if bp.movement_type != BallMovementType::Movable {
+                            continue;
+                        }
+                        self.balls[ball].property.v =
+                            calcuate_v_after_hit(&hit.hit_type, bp, opponent_bp);
+                        result.events.push(HitEvent::Bounce { ball, opponent });
+                    }
+                }
+            }
+        }
+        self.travel(remaining);
+        self.time += 1;
+        // NOTE: The level fails once every movable ball has lost its energy
+        if was_moving && !self.balls.iter().any(|ball| ball.is_moving()) {
+            self.outcome = Some(SimulationOutcome::Failure);
+            result.outcome = self.outcome;
+        }
+        result
+    }
+    // NOTE: Run a copy of the simulation with every ball started and collect the path of the target
+    // balls until max_ticks passed, max_hits hits of the targets happened or the level finished.
+    pub fn predict(&self, targets: &[usize], max_ticks: u32, max_hits: u32) -> Vec<Vec<Vec2>> {
+        let mut simulation = self.clone();
+        simulation.start();
+        let mut paths: Vec<Vec<Vec2>> = targets
+            .iter()
+            .map(|index| vec![simulation.balls[*index].property.pos])
+            .collect();
+        let mut hits: u32 = 0;
+        for _ in 0..max_ticks {
+            let result = simulation.step();
+            for (path, index) in paths.iter_mut().zip(targets.iter()) {
+                path.push(simulation.balls[*index].property.pos);
+            }
+            hits += result
+                .events
+                .iter()
+                .filter(|event| match event {
+                    HitEvent::Bounce { ball, .. }
+                    | HitEvent::WallBounce { ball, .. }
+                    | HitEvent::Success { ball }
+                    | HitEvent::Failure { ball } => targets.contains(ball),
+                })
+                .count() as u32;
+            if result.outcome.is_some() || hits >= max_hits {
+                break;
+            }
+        }
+        paths
+    }
+    fn apply_forces(&mut self) {
+        let wells: Vec<(usize, Vec2, f32, WellParams)> = self
+            .balls
+            .iter()
+            .enumerate()
+            .filter(|(_, ball)| ball.is_running)
+            .filter_map(|(index, ball)| {
+                ball.well
+                    .clone()
+                    .map(|well| (index, ball.property.pos, ball.property.radius, well))
+            })
+            .collect();
+        for (index, ball) in self.balls.iter_mut().enumerate() {
+            if !ball.is_running || ball.property.movement_type != BallMovementType::Movable {
+                continue;
+            }
+            let mut v = ball.property.v + self.gravity;
+            for (well_index, center, radius, well) in wells.iter() {
+                if *well_index != index {
+                    v += well.acceleration(*center, *radius, ball.property.pos);
+                }
+            }
+            for zone in self.zones.iter() {
+                if zone.shape.contains(ball.property.pos) {
+                    v = zone.effect.apply(v);
+                }
+            }
+            ball.property.v = v;
+        }
+    }
+    fn travel(&mut self, duration: f32) {
+        if duration <= 0.0 {
+            return;
+        }
+        for ball in self.balls.iter_mut() {
+            if ball.is_running {
+                ball.travel(duration);
+            }
+        }
+    }
+}
+
+fn clamp_range(value: f32, range: (f32, f32)) -> f32 {
+    value.clamp(range.0, range.1)
+}
+
+fn apply_launch(v: Vec2, force: Option<f32>, angle: Option<f32>) -> Vec2 {
+    match (force, angle) {
+        (Some(force), Some(angle)) => Vec2::from_angle(angle.to_radians()) * force,
+        (Some(force), None) => v.normalize_or_zero() * force,
+        (None, Some(angle)) => Vec2::from_angle(angle.to_radians()) * v.length(),
+        (None, None) => v,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LEVEL: &str = r#"(
+        name: "TEST",
+        gravity: (0.0, -0.05),
+        balls: [
+            (ball_type: Stone, property: (pos: (0.0, 0.0), radius: 320.0, movement_type: FixedReversed)),
+            (
+                ball_type: Stone,
+                property: (pos: (-200.0, 0.0), v: (8.0, 0.0), radius: 15.0, movement_type: Movable, damping: 0.001),
+                control_params: (force: Some((4.0, 12.0)), angle: Some((-45.0, 45.0))),
+            ),
+            (ball_type: Stone, property: (pos: (60.0, 40.0), radius: 20.0, movement_type: Movable)),
+            (ball_type: Goal, property: (pos: (200.0, 100.0), radius: 30.0, movement_type: Fixed)),
+            (ball_type: Bomb, property: (pos: (0.0, -150.0), radius: 25.0, movement_type: Fixed)),
+        ],
+        walls: [Segment(start: (-100.0, 200.0), end: (100.0, 120.0))],
+    )"#;
+    const MAX_TICKS: u32 = 20000;
+
+    fn run(level: &LevelConfig, controls: &ControlValues) -> (Vec<(u32, HitEvent)>, Simulation) {
+        let mut simulation = Simulation::new(level, controls);
+        simulation.start();
+        let mut events = Vec::new();
+        for _ in 0..MAX_TICKS {
+            let result = simulation.step();
+            events.extend(
+                result
+                    .events
+                    .into_iter()
+                    .map(|event| (simulation.time(), event)),
+            );
+            if result.outcome.is_some() {
+                break;
+            }
+        }
+        (events, simulation)
+    }
+
+    #[test]
+    fn same_input_gives_the_same_run() {
+        let level = LevelConfig::from_bytes("test", LEVEL.as_bytes()).unwrap();
+        let mut controls = ControlValues::from_level(&level);
+        controls.set(&level, 1, ControlField::Angle, 17.0);
+        let (events1, simulation1) = run(&level, &controls);
+        let (events2, simulation2) = run(&level, &controls);
+        assert!(simulation1.outcome().is_some());
+        assert!(!events1.is_empty());
+        assert_eq!(events1, events2);
+        assert_eq!(simulation1.time(), simulation2.time());
+        assert_eq!(simulation1.outcome(), simulation2.outcome());
+        for (ball1, ball2) in simulation1.balls().iter().zip(simulation2.balls().iter()) {
+            assert_eq!(ball1.property.pos.to_array(), ball2.property.pos.to_array());
+            assert_eq!(ball1.property.v.to_array(), ball2.property.v.to_array());
+            assert_eq!(ball1.is_running, ball2.is_running);
+        }
+    }
+
+    #[test]
+    fn position_is_where_the_simulation_places_the_ball() {
+        let level = LevelConfig::from_bytes(
+            "test",
+            r#"(
+                name: "TEST",
+                balls: [
+                    (ball_type: Stone, property: (pos: (0.0, 0.0), radius: 320.0, movement_type: FixedReversed)),
+                    (
+                        ball_type: Stone,
+                        property: (pos: (-100.0, 0.0), v: (4.0, 0.0), radius: 10.0, movement_type: Movable),
+                        control_params: (x: Some((-150.0, -50.0)), angle: Some((-45.0, 45.0))),
+                    ),
+                ],
+            )"#
+            .as_bytes(),
+        )
+        .unwrap();
+        let mut controls = ControlValues::from_level(&level);
+        assert_eq!(controls.position(&level, 1), Some(Vec2::new(-100.0, 0.0)));
+        controls.x[0] = -500.0;
+        let placed = controls.position(&level, 1).unwrap();
+        assert_eq!(placed, Vec2::new(-150.0, 0.0));
+        let simulation = Simulation::new(&level, &controls);
+        assert_eq!(simulation.ball(1).unwrap().property.pos, placed);
+        assert_eq!(controls.position(&level, 2), None);
+    }
+
+    // NOTE: A head-on hit of a launched stone on a resting one, with no gravity
+    fn head_on(property1: &str, property2: &str) -> LevelConfig {
+        let level = format!(
+            r#"(
+                name: "TEST",
+                gravity: (0.0, 0.0),
+                balls: [
+                    (ball_type: Stone, property: (pos: (0.0, 0.0), radius: 320.0, movement_type: FixedReversed)),
+                    (ball_type: Stone, property: (pos: (-100.0, 0.0), v: (4.0, 0.0), radius: 10.0, movement_type: Movable{property1})),
+                    (ball_type: Stone, property: (pos: (0.0, 0.0), radius: 10.0, movement_type: Movable{property2})),
+                ],
+            )"#
+        );
+        LevelConfig::from_bytes("test", level.as_bytes()).unwrap()
+    }
+
+    fn velocities_after_first_bounce(level: &LevelConfig) -> (Vec2, Vec2) {
+        let mut simulation = Simulation::new(level, &ControlValues::from_level(level));
+        simulation.start();
+        for _ in 0..MAX_TICKS {
+            let result = simulation.step();
+            if result.events.contains(&HitEvent::Bounce {
+                ball: 1,
+                opponent: 2,
+            }) {
+                let v1 = simulation.ball(1).unwrap().property.v;
+                let v2 = simulation.ball(2).unwrap().property.v;
+                return (v1, v2);
+            }
+        }
+        panic!("the stones never met");
+    }
+
+    fn assert_near(v: Vec2, expected: Vec2) {
+        assert!((v - expected).length() < 1e-4, "{v} != {expected}");
+    }
+
+    #[test]
+    fn restitution_keeps_momentum_and_scales_the_relative_speed() {
+        let level = head_on(", restitution: 0.5", ", restitution: 0.5");
+        let (v1, v2) = velocities_after_first_bounce(&level);
+        // NOTE: The pair restitution is 0.25, so the stones part at a quarter of the closing speed
+        assert_near(v1, Vec2::new(1.5, 0.0));
+        assert_near(v2, Vec2::new(2.5, 0.0));
+    }
+
+    #[test]
+    fn mass_and_density_weigh_the_bounce() {
+        let expected = (Vec2::new(-2.0, 0.0), Vec2::new(2.0, 0.0));
+        let by_mass = head_on(", mass: Some(1.0)", ", mass: Some(3.0)");
+        let (v1, v2) = velocities_after_first_bounce(&by_mass);
+        assert_near(v1, expected.0);
+        assert_near(v2, expected.1);
+        let by_density = head_on("", ", density: 3.0");
+        let (v1, v2) = velocities_after_first_bounce(&by_density);
+        assert_near(v1, expected.0);
+        assert_near(v2, expected.1);
+    }
+
+    #[test]
+    fn damping_brings_a_ball_to_rest() {
+        let level = head_on(", damping: 0.05", ", damping: 0.05");
+        let mut first = Simulation::new(&level, &ControlValues::from_level(&level));
+        first.start();
+        let mut second = Simulation::new(&level, &ControlValues::from_level(&level));
+        second.start();
+        for _ in 0..MAX_TICKS {
+            first.step();
+            second.step();
+        }
+        let ball = first.ball(1).unwrap();
+        assert_eq!(ball.property.v, Vec2::ZERO);
+        // NOTE: 4 / 0.05 is the farthest a damped stone can slide, short of the resting stone
+        assert!(ball.property.pos.x > -100.0 && ball.property.pos.x < -20.0);
+        assert_eq!(
+            ball.property.pos.to_array(),
+            second.ball(1).unwrap().property.pos.to_array()
+        );
+    }
+
+    #[test]
+    fn friction_only_slows_the_tangential_velocity() {
+        let v = calcuate_v_after_hit(
+            &HitType::Outside,
+            &BallProperty {
+                v: Vec2::new(-3.0, 4.0),
+                friction: 0.5,
+                ..BallProperty::new(Vec2::new(10.0, 0.0), 10.0, BallMovementType::Movable)
+            },
+            &BallProperty {
+                friction: 0.5,
+                ..BallProperty::new(Vec2::ZERO, 0.0, BallMovementType::Fixed)
+            },
+        );
+        assert_near(v, Vec2::new(3.0, 2.0));
+    }
+
+    #[test]
+    fn controls_are_clamped_to_their_ranges() {
+        let level = LevelConfig::from_bytes("test", LEVEL.as_bytes()).unwrap();
+        let mut controls = ControlValues::from_level(&level);
+        controls.force[0] = 100.0;
+        let simulation = Simulation::new(&level, &controls);
+        let v = simulation.ball(1).unwrap().property.v;
+        assert!((v.length() - 12.0).abs() < 1e-4);
+    }
+}
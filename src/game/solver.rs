@@ -0,0 +1,349 @@
+use super::{
+    level_builder::LevelConfig,
+    par::ParConfig,
+    simulation::{ControlField, ControlValues, Simulation, SimulationOutcome},
+};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+const CONTROL_FIELDS: [ControlField; 4] = [
+    ControlField::X,
+    ControlField::Y,
+    ControlField::Force,
+    ControlField::Angle,
+];
+const REFINE_OFFSETS: [f32; 3] = [-1.0, 0.0, 1.0];
+const PAR_TIME_SLACK: f32 = 1.2;
+const SOLVER_SEED: u64 = 0;
+
+#[derive(Debug, Clone)]
+pub struct SolverConfig {
+    pub grid_steps: usize,
+    pub max_grid_samples: usize,
+    pub refine_rounds: usize,
+    pub refine_candidates: usize,
+    pub max_refine_offsets: usize,
+    pub max_ticks: u32,
+}
+
+impl Default for SolverConfig {
+    fn default() -> Self {
+        Self {
+            grid_steps: 9,
+            max_grid_samples: 4096,
+            refine_rounds: 4,
+            refine_candidates: 4,
+            max_refine_offsets: 80,
+            max_ticks: 3000,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Solution {
+    pub values: ControlValues,
+    pub time: u32,
+    pub collisions: u32,
+}
+
+impl Solution {
+    pub fn total_force(&self) -> f32 {
+        self.values.force.iter().sum()
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SolverReport {
+    pub grid_samples: usize,
+    pub grid_wins: usize,
+    pub refine_samples: usize,
+    pub fastest: Option<Solution>,
+}
+
+impl SolverReport {
+    pub fn is_solvable(&self) -> bool {
+        self.fastest.is_some()
+    }
+    // NOTE: Only the uniform grid counts, the refined samples are biased toward the winning regions
+    pub fn win_fraction(&self) -> f32 {
+        if self.grid_samples == 0 {
+            return 0.0;
+        }
+        self.grid_wins as f32 / self.grid_samples as f32
+    }
+    pub fn suggested_par(&self) -> Option<ParConfig> {
+        let fastest = self.fastest.as_ref()?;
+        Some(ParConfig {
+            time: (fastest.time as f32 * PAR_TIME_SLACK).ceil() as u32,
+            force: None,
+            collisions: Some(fastest.collisions),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct ControlDim {
+    ball: usize,
+    field: ControlField,
+    range: (f32, f32),
+}
+
+impl ControlDim {
+    fn value_at(&self, step: usize, steps: usize) -> f32 {
+        if steps <= 1 {
+            return (self.range.0 + self.range.1) * 0.5;
+        }
+        self.range.0 + (self.range.1 - self.range.0) * step as f32 / (steps - 1) as f32
+    }
+    fn cell_size(&self, steps: usize) -> f32 {
+        (self.range.1 - self.range.0) / steps.saturating_sub(1).max(1) as f32
+    }
+}
+
+// NOTE: Sample the control space on a grid first, then search around the fastest wins with a
+// finer grid each round.
+pub fn solve(level: &LevelConfig, config: &SolverConfig) -> SolverReport {
+    let dims = control_dims(level);
+    let steps = grid_steps(dims.len(), config);
+    let base = ControlValues::from_level(level);
+    let mut report = SolverReport::default();
+    let mut wins: Vec<(Vec<f32>, Solution)> = vec![];
+    let mut rng = StdRng::seed_from_u64(SOLVER_SEED);
+    for point in grid_points(&dims, steps, config.max_grid_samples, &mut rng) {
+        report.grid_samples += 1;
+        if let Some(solution) = run(level, &apply_point(level, &base, &dims, &point), config) {
+            report.grid_wins += 1;
+            wins.push((point, solution));
+        }
+    }
+    let mut cell_sizes: Vec<f32> = dims.iter().map(|dim| dim.cell_size(steps)).collect();
+    for _ in 0..config.refine_rounds {
+        wins.sort_by_key(|(_, solution)| solution.time);
+        wins.truncate(config.refine_candidates);
+        cell_sizes.iter_mut().for_each(|size| *size *= 0.5);
+        let candidates: Vec<Vec<f32>> = wins.iter().map(|(point, _)| point.clone()).collect();
+        for center in candidates.iter() {
+            for offsets in refine_offsets(dims.len(), config.max_refine_offsets, &mut rng) {
+                let point: Vec<f32> = dims
+                    .iter()
+                    .zip(center.iter().zip(offsets.iter().zip(cell_sizes.iter())))
+                    .map(|(dim, (value, (offset, size)))| {
+                        (value + offset * size).clamp(dim.range.0, dim.range.1)
+                    })
+                    .collect();
+                report.refine_samples += 1;
+                if let Some(solution) =
+                    run(level, &apply_point(level, &base, &dims, &point), config)
+                {
+                    wins.push((point, solution));
+                }
+            }
+        }
+    }
+    report.fastest = wins
+        .into_iter()
+        .map(|(_, solution)| solution)
+        .min_by_key(|solution| solution.time);
+    report
+}
+
+fn control_dims(level: &LevelConfig) -> Vec<ControlDim> {
+    let mut dims = vec![];
+    for (ball, ball_config) in level.balls.iter().enumerate() {
+        for field in CONTROL_FIELDS {
+            if let Some(range) = field.range(&ball_config.control_params) {
+                dims.push(ControlDim { ball, field, range });
+            }
+        }
+    }
+    dims
+}
+
+// NOTE: Use fewer steps per dimension for levels with many controls to bound the grid size
+fn grid_steps(dims: usize, config: &SolverConfig) -> usize {
+    let mut steps = config.grid_steps.max(1);
+    while steps > 2 && steps.pow(dims as u32) > config.max_grid_samples {
+        steps -= 1;
+    }
+    steps
+}
+
+// NOTE: Even 2 steps per dimension overflow the sample cap for levels with many controls, those
+// are sampled uniformly at seeded random points instead.
+fn grid_points(
+    dims: &[ControlDim],
+    steps: usize,
+    max_samples: usize,
+    rng: &mut StdRng,
+) -> Vec<Vec<f32>> {
+    let total = steps.checked_pow(dims.len() as u32).unwrap_or(usize::MAX);
+    if total > max_samples {
+        return (0..max_samples)
+            .map(|_| {
+                dims.iter()
+                    .map(|dim| rng.gen_range(dim.range.0..=dim.range.1))
+                    .collect()
+            })
+            .collect();
+    }
+    (0..total)
+        .map(|index| {
+            dims.iter()
+                .enumerate()
+                .map(|(dim_index, dim)| {
+                    let step = index / steps.pow(dim_index as u32) % steps;
+                    dim.value_at(step, steps)
+                })
+                .collect()
+        })
+        .collect()
+}
+
+// NOTE: The neighbours of the center without the center itself, it is evaluated already. Levels
+// with many controls have too many neighbours to try, so a seeded random subset of them is used.
+fn refine_offsets(dims: usize, max_offsets: usize, rng: &mut StdRng) -> Vec<Vec<f32>> {
+    let total = REFINE_OFFSETS
+        .len()
+        .checked_pow(dims as u32)
+        .unwrap_or(usize::MAX);
+    if total - 1 > max_offsets {
+        return (0..max_offsets)
+            .map(|_| loop {
+                let offsets: Vec<f32> = (0..dims)
+                    .map(|_| REFINE_OFFSETS[rng.gen_range(0..REFINE_OFFSETS.len())])
+                    .collect();
+                if offsets.iter().any(|offset| *offset != 0.0) {
+                    break offsets;
+                }
+            })
+            .collect();
+    }
+    (0..total)
+        .map(|index| {
+            (0..dims)
+                .map(|dim_index| {
+                    REFINE_OFFSETS
+                        [index / REFINE_OFFSETS.len().pow(dim_index as u32) % REFINE_OFFSETS.len()]
+                })
+                .collect::<Vec<f32>>()
+        })
+        .filter(|offsets| offsets.iter().any(|offset| *offset != 0.0))
+        .collect()
+}
+
+fn apply_point(
+    level: &LevelConfig,
+    base: &ControlValues,
+    dims: &[ControlDim],
+    point: &[f32],
+) -> ControlValues {
+    let mut values = base.clone();
+    for (dim, value) in dims.iter().zip(point.iter()) {
+        values.set(level, dim.ball, dim.field, *value);
+    }
+    values
+}
+
+fn run(level: &LevelConfig, values: &ControlValues, config: &SolverConfig) -> Option<Solution> {
+    let mut simulation = Simulation::new(level, values);
+    simulation.start();
+    let mut collisions: u32 = 0;
+    for _ in 0..config.max_ticks {
+        let result = simulation.step();
+        collisions += result.contacts;
+        match result.outcome {
+            Some(SimulationOutcome::Success) => {
+                return Some(Solution {
+                    values: values.clone(),
+                    time: simulation.time(),
+                    collisions,
+                })
+            }
+            Some(SimulationOutcome::Failure) => return None,
+            None => {}
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::level_builder::{LevelCatalog, CATALOG_EXT, LEVEL_EXT};
+    use std::{fs, path::PathBuf};
+
+    fn level_dir() -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("assets/levels")
+    }
+
+    #[test]
+    fn every_shipped_level_is_solvable() {
+        let bytes = fs::read(level_dir().join(format!("main.{}", CATALOG_EXT))).unwrap();
+        let catalog: LevelCatalog = ron::de::from_bytes(&bytes).unwrap();
+        assert!(!catalog.levels.is_empty());
+        for code in catalog.levels.iter() {
+            let bytes = fs::read(level_dir().join(format!("{}.{}", code, LEVEL_EXT))).unwrap();
+            let level = LevelConfig::from_bytes(code, &bytes).unwrap();
+            let report = solve(&level, &SolverConfig::default());
+            let fastest = report
+                .fastest
+                .unwrap_or_else(|| panic!("level {} is not solvable", code));
+            let mut simulation = Simulation::new(&level, &fastest.values);
+            simulation.start();
+            while simulation.outcome().is_none() && simulation.time() < fastest.time {
+                simulation.step();
+            }
+            assert_eq!(
+                simulation.outcome(),
+                Some(SimulationOutcome::Success),
+                "the solution of level {} does not win",
+                code
+            );
+        }
+    }
+
+    #[test]
+    fn grid_falls_back_to_random_points_over_the_cap() {
+        let config = SolverConfig::default();
+        let dim = |range| ControlDim {
+            ball: 1,
+            field: ControlField::Angle,
+            range,
+        };
+        let dims = vec![dim((-45.0, 45.0)), dim((4.0, 12.0))];
+        let steps = grid_steps(dims.len(), &config);
+        let mut rng = StdRng::seed_from_u64(SOLVER_SEED);
+        let points = grid_points(&dims, steps, config.max_grid_samples, &mut rng);
+        assert_eq!(points.len(), steps * steps);
+        assert!(points.contains(&vec![-45.0, 4.0]) && points.contains(&vec![45.0, 12.0]));
+
+        let dims: Vec<ControlDim> = (0..13).map(|_| dim((-45.0, 45.0))).collect();
+        let steps = grid_steps(dims.len(), &config);
+        assert!(steps.pow(dims.len() as u32) > config.max_grid_samples);
+        let points = grid_points(&dims, steps, config.max_grid_samples, &mut rng);
+        assert_eq!(points.len(), config.max_grid_samples);
+        assert!(points
+            .iter()
+            .flatten()
+            .all(|value| (-45.0..=45.0).contains(value)));
+        let mut rng = StdRng::seed_from_u64(SOLVER_SEED);
+        let first = grid_points(&dims, steps, config.max_grid_samples, &mut rng);
+        let mut rng = StdRng::seed_from_u64(SOLVER_SEED);
+        let second = grid_points(&dims, steps, config.max_grid_samples, &mut rng);
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn refine_offsets_skip_the_center() {
+        let mut rng = StdRng::seed_from_u64(SOLVER_SEED);
+        let offsets = refine_offsets(2, 80, &mut rng);
+        assert_eq!(offsets.len(), 8);
+        assert!(offsets
+            .iter()
+            .all(|offset| offset.iter().any(|v| *v != 0.0)));
+        let offsets = refine_offsets(12, 80, &mut rng);
+        assert_eq!(offsets.len(), 80);
+        assert!(offsets
+            .iter()
+            .all(|offset| offset.iter().any(|v| *v != 0.0)));
+    }
+}
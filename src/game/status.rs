@@ -1,16 +1,20 @@
 use bevy::{prelude::*, utils::HashSet};
 use chrono::Local;
 
-use super::{level_builder::LevelConfig, BallControlDisplay, LevelHistory};
+use super::{
+    level_builder::LevelConfig,
+    replay::ReplayStatus,
+    simulation::{ControlValues, Simulation},
+    BallControlDisplay, LevelHistory,
+};
 
-#[derive(PartialEq, Default, Debug)]
+#[derive(Clone, Copy, PartialEq, Default, Debug)]
 pub enum StatusMode {
     #[default]
     Demo,
     Setup,
     Deploying,
     Running,
-    Paused,
     Done,
 }
 
@@ -22,6 +26,16 @@ pub struct GameStatus {
     pub control_displays: Vec<BallControlDisplay>,
     pub current_history: LevelHistory,
     pub current_level: LevelConfig,
+    pub level_code: String,
+    pub simulation: Simulation,
+    pub control_values: ControlValues,
+    pub replay: Option<ReplayStatus>,
+    pub ghost: Option<Simulation>,
+    pub is_test_play: bool,
+    pub resume_mode: Option<StatusMode>,
+    pub retry_values: Option<ControlValues>,
+    pub aimed_ball: Option<usize>,
+    pub is_in_pause_settings: bool,
 }
 
 impl GameStatus {
@@ -29,10 +43,6 @@ impl GameStatus {
         self.current_history = LevelHistory::default()
     }
 
-    pub fn tick(&mut self) {
-        self.current_history.time += 1;
-    }
-
     pub fn sumbit_history(&mut self, is_clear: bool) {
         self.current_history.is_clear = is_clear;
         self.current_history.created_at = Local::now().format("%Y-%m-%d_%H:%M:%S%.9f").to_string();
@@ -53,4 +63,15 @@ impl GameStatus {
     pub fn set_refreshed(&mut self, target: u8) {
         self.refreshed_targets.insert(target);
     }
+
+    // NOTE: Retries are live attempts starting from the controls of the last one
+    pub fn prepare_retry(&mut self) {
+        self.retry_values = Some(ControlValues::from(&self.current_history));
+        self.replay = None;
+    }
+}
+
+// NOTE: The settings opened from the pause leave the paused level in place behind them
+pub fn is_in_pause_settings(game_status: Res<GameStatus>) -> bool {
+    game_status.is_in_pause_settings
 }
@@ -0,0 +1,211 @@
+use super::*;
+use bevy_tweening::Animator;
+
+pub const TIME_SCALES: [f32; 8] = [0.0, 0.1, 0.25, 0.5, 1.0, 2.0, 4.0, 8.0];
+const STOP_SCALE_INDEX: usize = 0;
+const NORMAL_SCALE_INDEX: usize = 4;
+// NOTE: The normal game steps the simulation every other refresh tick
+const STEPS_PER_TICK: f32 = 0.5;
+
+// NOTE: The scale changes how many simulation ticks run per refresh tick, never the tick itself,
+// so the outcome of an attempt is the same at every scale. The zero scale stops the attempt, it
+// then only advances by single steps.
+#[derive(Resource, Debug)]
+pub struct TimeScale {
+    scale_index: usize,
+    resume_index: usize,
+    pending_steps: f32,
+    frame_steps: u32,
+}
+
+impl Default for TimeScale {
+    fn default() -> Self {
+        Self {
+            scale_index: NORMAL_SCALE_INDEX,
+            resume_index: NORMAL_SCALE_INDEX,
+            pending_steps: 0.0,
+            frame_steps: 0,
+        }
+    }
+}
+
+impl TimeScale {
+    pub fn scale(&self) -> f32 {
+        TIME_SCALES[self.scale_index]
+    }
+    pub fn is_normal(&self) -> bool {
+        self.scale_index == NORMAL_SCALE_INDEX
+    }
+    pub fn is_stopped(&self) -> bool {
+        self.scale_index == STOP_SCALE_INDEX
+    }
+    pub fn speed_up(&mut self) {
+        self.scale_index = (self.scale_index + 1).min(TIME_SCALES.len() - 1);
+    }
+    // NOTE: Slowing down never stops the attempt, only the stop toggle does
+    pub fn slow_down(&mut self) {
+        self.scale_index = self.scale_index.saturating_sub(1).max(STOP_SCALE_INDEX + 1);
+    }
+    pub fn toggle_stop(&mut self) {
+        if self.is_stopped() {
+            self.scale_index = self.resume_index;
+        } else {
+            self.resume_index = self.scale_index;
+            self.scale_index = STOP_SCALE_INDEX;
+        }
+    }
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+    pub fn request_frame_step(&mut self) {
+        self.frame_steps += 1;
+    }
+    // NOTE: Accumulate fractional steps so slow scales still advance the simulation, and run the
+    // requested single steps only while stopped
+    pub fn steps_for_tick(&mut self) -> u32 {
+        if self.is_stopped() {
+            return std::mem::take(&mut self.frame_steps);
+        }
+        self.frame_steps = 0;
+        self.pending_steps += self.scale() * STEPS_PER_TICK;
+        let steps = self.pending_steps.floor();
+        self.pending_steps -= steps;
+        steps as u32
+    }
+}
+
+// NOTE: Ball tweens follow the scale and hold still while stopped
+pub fn sync_ball_animators(
+    time_scale: Res<TimeScale>,
+    mut animator_query: Query<&mut Animator<Ball>>,
+) {
+    let scale = time_scale.scale();
+    for mut animator in animator_query.iter_mut() {
+        if animator.speed() != scale {
+            animator.set_speed(scale);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::simulation::{ControlValues, HitEvent, Simulation};
+
+    const LEVEL: &str = r#"(
+        name: "TEST",
+        gravity: (0.0, -0.05),
+        balls: [
+            (ball_type: Stone, property: (pos: (0.0, 0.0), radius: 320.0, movement_type: FixedReversed)),
+            (ball_type: Stone, property: (pos: (-200.0, 0.0), v: (8.0, 1.0), radius: 15.0, movement_type: Movable, damping: 0.001)),
+            (ball_type: Stone, property: (pos: (60.0, 40.0), radius: 20.0, movement_type: Movable)),
+            (ball_type: Goal, property: (pos: (200.0, 100.0), radius: 30.0, movement_type: Fixed)),
+        ],
+        walls: [Segment(start: (-100.0, 200.0), end: (100.0, 120.0))],
+    )"#;
+    const MAX_REFRESH_TICKS: u32 = 500000;
+
+    fn simulation() -> Simulation {
+        let level = LevelConfig::from_bytes("test", LEVEL.as_bytes()).unwrap();
+        let mut simulation = Simulation::new(&level, &ControlValues::from_level(&level));
+        simulation.start();
+        simulation
+    }
+
+    // NOTE: Runs the attempt the way the running phase does, a number of steps per refresh tick
+    fn run_at(scale_index: usize) -> (Vec<(u32, HitEvent)>, Simulation, u32) {
+        let mut time_scale = TimeScale {
+            scale_index,
+            ..default()
+        };
+        let mut simulation = simulation();
+        let mut events = Vec::new();
+        for refresh_tick in 1..=MAX_REFRESH_TICKS {
+            for _ in 0..time_scale.steps_for_tick() {
+                let result = simulation.step();
+                let time = simulation.time();
+                events.extend(result.events.into_iter().map(|event| (time, event)));
+                if result.outcome.is_some() {
+                    return (events, simulation, refresh_tick);
+                }
+            }
+        }
+        panic!("the attempt never ended at {}", TIME_SCALES[scale_index]);
+    }
+
+    #[test]
+    fn every_scale_gives_the_same_attempt() {
+        let (events, simulation, refresh_ticks) = run_at(NORMAL_SCALE_INDEX);
+        assert!(simulation.outcome().is_some());
+        assert!(!events.is_empty());
+        for scale_index in STOP_SCALE_INDEX + 1..TIME_SCALES.len() {
+            let (scaled_events, scaled_simulation, scaled_refresh_ticks) = run_at(scale_index);
+            assert_eq!(scaled_events, events);
+            assert_eq!(scaled_simulation.time(), simulation.time());
+            assert_eq!(scaled_simulation.outcome(), simulation.outcome());
+            for (ball, scaled_ball) in simulation.balls().iter().zip(scaled_simulation.balls()) {
+                assert_eq!(
+                    ball.property.pos.to_array(),
+                    scaled_ball.property.pos.to_array()
+                );
+                assert_eq!(
+                    ball.property.v.to_array(),
+                    scaled_ball.property.v.to_array()
+                );
+            }
+            // NOTE: Only the number of refresh ticks the attempt takes follows the scale
+            let expected = refresh_ticks as f32 / TIME_SCALES[scale_index];
+            assert!(
+                (scaled_refresh_ticks as f32 - expected).abs() <= expected * 0.01 + 2.0,
+                "{scaled_refresh_ticks} refresh ticks at {}, expected {expected}",
+                TIME_SCALES[scale_index]
+            );
+        }
+    }
+
+    #[test]
+    fn stopped_attempt_moves_one_step_per_request() {
+        let mut time_scale = TimeScale::default();
+        let mut simulation = simulation();
+        time_scale.toggle_stop();
+        assert!(time_scale.is_stopped());
+        for _ in 0..10 {
+            assert_eq!(time_scale.steps_for_tick(), 0);
+        }
+        assert_eq!(simulation.time(), 0);
+        for expected_time in 1..=3 {
+            time_scale.request_frame_step();
+            for _ in 0..time_scale.steps_for_tick() {
+                simulation.step();
+            }
+            assert_eq!(simulation.time(), expected_time);
+            assert_eq!(time_scale.steps_for_tick(), 0);
+        }
+        time_scale.toggle_stop();
+        assert!(time_scale.is_normal());
+    }
+
+    #[test]
+    fn frame_steps_are_dropped_while_running() {
+        let mut time_scale = TimeScale::default();
+        time_scale.request_frame_step();
+        time_scale.request_frame_step();
+        let steps: u32 = (0..4).map(|_| time_scale.steps_for_tick()).sum();
+        assert_eq!(steps, 2);
+        time_scale.toggle_stop();
+        assert_eq!(time_scale.steps_for_tick(), 0);
+    }
+
+    #[test]
+    fn slowing_down_never_stops() {
+        let mut time_scale = TimeScale::default();
+        for _ in 0..TIME_SCALES.len() {
+            time_scale.slow_down();
+        }
+        assert!(!time_scale.is_stopped());
+        assert_eq!(time_scale.scale(), TIME_SCALES[STOP_SCALE_INDEX + 1]);
+        time_scale.toggle_stop();
+        time_scale.toggle_stop();
+        assert_eq!(time_scale.scale(), TIME_SCALES[STOP_SCALE_INDEX + 1]);
+    }
+}
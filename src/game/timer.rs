@@ -16,3 +16,9 @@ pub const GAME_BUILD_DELAY_SECS: f32 = 0.05;
 pub const GAME_REFRESH_FRAME_SECS: f32 = 0.01;
 pub const GAME_THROTTLE_SECS: f32 = 0.2;
 pub const GAME_SCOREBOARD_SECS: f32 = 0.01;
+// NOTE: The simulation steps every other refresh tick
+pub const GAME_TICK_SECS: f32 = GAME_REFRESH_FRAME_SECS * 2.0;
+
+pub fn ticks_to_secs(ticks: u32) -> f32 {
+    ticks as f32 * GAME_TICK_SECS
+}
@@ -0,0 +1,110 @@
+use super::{
+    aiming::launchable_stones,
+    level_builder::LevelConfig,
+    simulation::{ControlValues, Simulation},
+    *,
+};
+use crate::app::ui;
+use bevy_prototype_lyon::prelude::*;
+use serde::{Deserialize, Serialize};
+
+const PREVIEW_DOT_R: f32 = ui::FONT_SIZE / 18.0;
+const PREVIEW_DOT_GAP: usize = 4;
+const PREVIEW_Z_LAYER: f32 = 0.8;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PreviewConfig {
+    pub enabled: bool,
+    pub max_ticks: u32,
+    pub max_hits: u32,
+}
+
+impl Default for PreviewConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_ticks: 120,
+            max_hits: 2,
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct TrajectoryPreview;
+
+pub fn refresh_trajectory_preview(
+    mut commands: Commands,
+    dyn_query: Query<Entity, With<GameDyn>>,
+    preview_query: Query<Entity, With<TrajectoryPreview>>,
+    game_status: Res<GameStatus>,
+    mut last_preview: Local<Option<(String, ControlValues)>>,
+) {
+    let level = &game_status.current_level;
+    let is_shown = game_status.mode == StatusMode::Deploying && level.preview.enabled;
+    let preview_key = Some((level.code.clone(), game_status.control_values.clone()));
+    if is_shown && *last_preview == preview_key && !preview_query.is_empty() {
+        return;
+    }
+    for entity in preview_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    if !is_shown {
+        *last_preview = None;
+        return;
+    }
+    *last_preview = preview_key;
+    let Ok(dyn_entity) = dyn_query.get_single() else {
+        return;
+    };
+    let targets = preview_targets(level);
+    let simulation = Simulation::new(level, &game_status.control_values);
+    let paths = simulation.predict(&targets, level.preview.max_ticks, level.preview.max_hits);
+    commands.entity(dyn_entity).with_children(|parent| {
+        parent
+            .spawn((
+                SpatialBundle {
+                    transform: Transform::from_xyz(0.0, 0.0, PREVIEW_Z_LAYER),
+                    ..default()
+                },
+                TrajectoryPreview,
+            ))
+            .with_children(|parent| {
+                for (index, path) in targets.iter().zip(paths.iter()) {
+                    let mut dots_builder = GeometryBuilder::new();
+                    for pos in path.iter().skip(1).step_by(PREVIEW_DOT_GAP) {
+                        dots_builder = dots_builder.add(&shapes::Circle {
+                            radius: PREVIEW_DOT_R,
+                            center: *pos,
+                        });
+                    }
+                    let color = level.balls[*index].ball_type.color();
+                    parent.spawn((
+                        ShapeBundle {
+                            path: dots_builder.build(),
+                            ..default()
+                        },
+                        Fill::color(color.with_alpha(0.6)),
+                    ));
+                }
+            });
+    });
+}
+
+// NOTE: Preview the stones which can be launched, or every movable stone if none of them can be.
+fn preview_targets(level: &LevelConfig) -> Vec<usize> {
+    let launchable = launchable_stones(level);
+    if !launchable.is_empty() {
+        return launchable;
+    }
+    level
+        .balls
+        .iter()
+        .enumerate()
+        .filter(|(_, ball)| {
+            ball.ball_type == BallType::Stone
+                && ball.property.movement_type == BallMovementType::Movable
+        })
+        .map(|(index, _)| index)
+        .collect()
+}
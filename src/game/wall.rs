@@ -0,0 +1,125 @@
+use crate::app::{theme, ui};
+use bevy::prelude::*;
+use bevy_prototype_lyon::prelude::*;
+use serde::{Deserialize, Serialize};
+
+const WALL_LINE_W: f32 = ui::FONT_SIZE / 6.0;
+const WALL_Z_LAYER: f32 = 0.5;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum WallShape {
+    Segment { start: Vec2, end: Vec2 },
+    Polyline { points: Vec<Vec2> },
+    Polygon { points: Vec<Vec2> },
+}
+
+impl WallShape {
+    pub fn segments(&self) -> Vec<(Vec2, Vec2)> {
+        match self {
+            WallShape::Segment { start, end } => vec![(*start, *end)],
+            WallShape::Polyline { points } => points.windows(2).map(|w| (w[0], w[1])).collect(),
+            WallShape::Polygon { points } => {
+                let mut segments: Vec<(Vec2, Vec2)> =
+                    points.windows(2).map(|w| (w[0], w[1])).collect();
+                if let (Some(first), Some(last)) = (points.first(), points.last()) {
+                    segments.push((*last, *first));
+                }
+                segments
+            }
+        }
+    }
+    pub fn validate(&self) -> Result<(), &'static str> {
+        match self {
+            WallShape::Segment { .. } => (),
+            WallShape::Polyline { points } => {
+                if points.len() < 2 {
+                    return Err("a polyline needs at least 2 points");
+                }
+            }
+            WallShape::Polygon { points } => {
+                if points.len() < 3 {
+                    return Err("a polygon needs at least 3 points");
+                }
+                if !is_convex(points) {
+                    return Err("a polygon should be convex");
+                }
+            }
+        }
+        if self
+            .segments()
+            .iter()
+            .any(|(start, end)| start.distance_squared(*end) == 0.0)
+        {
+            return Err("a wall segment should not be empty");
+        }
+        Ok(())
+    }
+}
+
+#[derive(Component, Debug)]
+pub struct Wall;
+
+impl Wall {
+    pub fn create_sprite(parent: &mut ChildBuilder, shape: WallShape) {
+        let mut path_builder = PathBuilder::new();
+        for (start, end) in shape.segments().iter() {
+            path_builder.move_to(*start);
+            path_builder.line_to(*end);
+        }
+        let stroke = Stroke {
+            color: theme::LIGHT_COLOR,
+            options: StrokeOptions::DEFAULT
+                .with_line_width(WALL_LINE_W)
+                .with_line_cap(LineCap::Round)
+                .with_line_join(LineJoin::Round),
+        };
+        if let WallShape::Polygon { points } = &shape {
+            let polygon = shapes::Polygon {
+                points: points.clone(),
+                closed: true,
+            };
+            parent.spawn((
+                ShapeBundle {
+                    path: GeometryBuilder::build_as(&polygon),
+                    spatial: SpatialBundle {
+                        transform: Transform::from_xyz(0.0, 0.0, WALL_Z_LAYER),
+                        ..default()
+                    },
+                    ..default()
+                },
+                Fill::color(theme::MUTE_COLOR.with_alpha(0.5)),
+            ));
+        }
+        parent.spawn((
+            ShapeBundle {
+                path: path_builder.build(),
+                spatial: SpatialBundle {
+                    transform: Transform::from_xyz(0.0, 0.0, WALL_Z_LAYER + 0.001),
+                    ..default()
+                },
+                ..default()
+            },
+            stroke,
+            Wall,
+        ));
+    }
+}
+
+fn is_convex(points: &[Vec2]) -> bool {
+    let mut sign = 0.0;
+    for i in 0..points.len() {
+        let p0 = points[i];
+        let p1 = points[(i + 1) % points.len()];
+        let p2 = points[(i + 2) % points.len()];
+        let cross = (p1 - p0).perp_dot(p2 - p1);
+        if cross == 0.0 {
+            continue;
+        }
+        if sign == 0.0 {
+            sign = cross.signum();
+        } else if cross.signum() != sign {
+            return false;
+        }
+    }
+    sign != 0.0
+}